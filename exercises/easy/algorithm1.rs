@@ -3,7 +3,11 @@
     This problem requires you to merge two ordered singly linked lists into one ordered singly linked list
 */
 
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
 use std::fmt::{self, Display, Formatter};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
 use std::ptr::NonNull;
 
 #[derive(Debug)]
@@ -17,6 +21,15 @@ impl<T> Node<T> {
         Node { val: t, next: None }
     }
 }
+
+// Safety invariants upheld by every method of `LinkedList`:
+// - every node reachable from `start` was allocated with `Box::into_raw` and is owned by
+//   exactly one list; it is freed exactly once, either by `pop_front`/`remove_at` or by `Drop`;
+// - `start` and `end` are both `None` when the list is empty, otherwise `end` points to the
+//   last reachable node and that node's `next` is `None`;
+// - `length` equals the number of nodes reachable from `start`.
+// Shared references handed out by `get`/`iter` borrow the list, mutable ones handed out by
+// `iter_mut` borrow it mutably, so no node can be freed or relinked while they are alive.
 #[derive(Debug)]
 struct LinkedList<T: Ord> {
     length: u32,
@@ -39,108 +52,514 @@ impl<T: Ord> LinkedList<T> {
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.length as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
     pub fn add(&mut self, obj: T) {
-        let mut node = Box::new(Node::new(obj));
-        node.next = None;
-        let node_ptr = Some(unsafe { NonNull::new_unchecked(Box::into_raw(node)) });
-        match self.end {
-            None => self.start = node_ptr,
-            Some(end_ptr) => unsafe { (*end_ptr.as_ptr()).next = node_ptr },
-        }
-        self.end = node_ptr;
+        self.link_back(Self::alloc_node(obj));
+    }
+
+    pub fn push_front(&mut self, obj: T) {
+        let node = Self::alloc_node(obj);
+        // SAFETY: `node` is freshly allocated and not yet reachable from any list.
+        unsafe { (*node.as_ptr()).next = self.start };
+        if self.end.is_none() {
+            self.end = Some(node);
+        }
+        self.start = Some(node);
+        self.length += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        // SAFETY: the node has just been unlinked, so this is the only owner left.
+        self.unlink_front()
+            .map(|node| unsafe { Box::from_raw(node.as_ptr()) }.val)
+    }
+
+    // Inserts `obj` so that it ends up at position `index`; panics if `index > len`.
+    pub fn insert_at(&mut self, index: usize, obj: T) {
+        assert!(
+            index <= self.len(),
+            "insertion index (is {}) should be <= len (is {})",
+            index,
+            self.len()
+        );
+        if index == 0 {
+            return self.push_front(obj);
+        }
+        if index == self.len() {
+            return self.add(obj);
+        }
+        let prev = self.node_at(index - 1).expect("index checked above");
+        let node = Self::alloc_node(obj);
+        // SAFETY: `prev` is a node of this list and is not the last one, so `end` is unchanged.
+        unsafe {
+            (*node.as_ptr()).next = (*prev.as_ptr()).next;
+            (*prev.as_ptr()).next = Some(node);
+        }
         self.length += 1;
     }
 
-    pub fn get(&mut self, index: i32) -> Option<&T> {
-        self.get_ith_node(self.start, index)
+    // Removes and returns the element at `index`, or `None` if it is out of range.
+    pub fn remove_at(&mut self, index: usize) -> Option<T> {
+        if index >= self.len() {
+            return None;
+        }
+        if index == 0 {
+            return self.pop_front();
+        }
+        let prev = self.node_at(index - 1)?;
+        // SAFETY: `prev` is not the last node (index < len), so `target` exists; after relinking
+        // it is no longer reachable and can be reclaimed.
+        unsafe {
+            let target = (*prev.as_ptr()).next?;
+            (*prev.as_ptr()).next = (*target.as_ptr()).next;
+            if self.end == Some(target) {
+                self.end = Some(prev);
+            }
+            self.length -= 1;
+            Some(Box::from_raw(target.as_ptr()).val)
+        }
+    }
+
+    pub fn reverse(&mut self) {
+        let mut prev: Option<NonNull<Node<T>>> = None;
+        let mut current = self.start;
+        while let Some(node) = current {
+            // SAFETY: every node is visited once and only its own `next` is rewritten.
+            unsafe {
+                current = (*node.as_ptr()).next;
+                (*node.as_ptr()).next = prev;
+            }
+            prev = Some(node);
+        }
+        std::mem::swap(&mut self.start, &mut self.end);
     }
 
-    fn get_ith_node(&mut self, node: Option<NonNull<Node<T>>>, index: i32) -> Option<&T> {
-        match node {
-            None => None,
-            Some(next_ptr) => match index {
-                0 => Some(unsafe { &(*next_ptr.as_ptr()).val }),
-                _ => self.get_ith_node(unsafe { (*next_ptr.as_ptr()).next }, index - 1),
-            },
+    pub fn get(&self, index: i32) -> Option<&T> {
+        if index < 0 {
+            return None;
         }
+        // SAFETY: the node belongs to this list and lives as long as the `&self` borrow.
+        self.node_at(index as usize)
+            .map(|node| unsafe { &(*node.as_ptr()).val })
     }
-    pub fn merge(list_a: LinkedList<T>, list_b: LinkedList<T>) -> Self {
+
+    pub fn get_mut(&mut self, index: i32) -> Option<&mut T> {
+        if index < 0 {
+            return None;
+        }
+        // SAFETY: the node belongs to this list and `&mut self` guarantees exclusive access.
+        self.node_at(index as usize)
+            .map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.start,
+            remaining: self.len(),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.start,
+            remaining: self.len(),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn merge(mut list_a: LinkedList<T>, mut list_b: LinkedList<T>) -> Self {
         // merge two linked lists, return a new sorted linked list
-        let length = list_a.length + list_b.length;
-        let (start, end) = if list_a.start.is_none() {
-            (list_b.start, list_b.end)
-        } else if list_b.start.is_none() {
-            (list_a.start, list_a.end)
-        } else {
-            let mut current = None;
-            let start = {
-                unsafe {
-                    if (*list_a.start.unwrap().as_ptr()).val < (*list_b.start.unwrap().as_ptr()).val
-                    {
-                        list_a.start
-                    } else {
-                        list_b.start
-                    }
-                }
+        // nodes are relinked rather than reallocated; on ties `list_a` goes first, so the merge is stable
+        let mut merged = Self::new();
+        loop {
+            let take_a = match (list_a.start, list_b.start) {
+                (None, None) => break,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                // SAFETY: both heads are live nodes of their lists.
+                (Some(a), Some(b)) => unsafe { (*a.as_ptr()).val <= (*b.as_ptr()).val },
             };
-            let mut current_a = list_a.start;
-            let mut current_b = list_b.start;
-            let mut temp = start;
-            for _ in 0..length {
-                if current_a.is_none() {
-                    unsafe {
-                        current = current_b;
-                        current_b = (*current_b.unwrap().as_ptr()).next;
-                    }
-                } else if current_b.is_none() {
-                    unsafe {
-                        current = current_a;
-                        current_a = (*current_a.unwrap().as_ptr()).next;
+            let node = if take_a {
+                list_a.unlink_front()
+            } else {
+                list_b.unlink_front()
+            };
+            merged.link_back(node.expect("head checked above"));
+        }
+        merged
+    }
+
+    // k-way merge of sorted lists through a min-heap of list heads, O(n log k);
+    // equal elements keep the order of the lists they came from
+    pub fn merge_all(lists: Vec<LinkedList<T>>) -> Self {
+        let mut lists = lists;
+        let mut heap = BinaryHeap::with_capacity(lists.len());
+        for (source, list) in lists.iter().enumerate() {
+            if let Some(node) = list.start {
+                heap.push(HeapEntry { node, source });
+            }
+        }
+        let mut merged = Self::new();
+        while let Some(HeapEntry { source, .. }) = heap.pop() {
+            let list = &mut lists[source];
+            let node = list.unlink_front().expect("heap entries point at list heads");
+            merged.link_back(node);
+            if let Some(node) = list.start {
+                heap.push(HeapEntry { node, source });
+            }
+        }
+        merged
+    }
+
+    pub fn sort(&mut self) {
+        self.sort_by(T::cmp)
+    }
+
+    // Stable bottom-up merge sort: runs of width 1, 2, 4, ... are merged pairwise by relinking
+    // nodes, so besides a few pointers no extra memory is used.
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if self.length < 2 {
+            return;
+        }
+        let mut width = 1;
+        loop {
+            let mut rest = self.start.take();
+            let mut head = None;
+            let mut tail: Option<NonNull<Node<T>>> = None;
+            let mut merges = 0;
+            // SAFETY: the runs are disjoint pieces of this list's own chain, every node is
+            // relinked exactly once per pass and the chain is reattached to `self` afterwards.
+            unsafe {
+                while let Some(left) = rest {
+                    let right = Self::split_after(left, width);
+                    rest = right.and_then(|right| Self::split_after(right, width));
+                    let (run_head, run_tail) = Self::merge_runs(left, right, &mut compare);
+                    match tail {
+                        None => head = Some(run_head),
+                        Some(tail) => (*tail.as_ptr()).next = Some(run_head),
                     }
-                } else {
-                    unsafe {
-                        if (*current_a.unwrap().as_ptr()).val < (*current_b.unwrap().as_ptr()).val {
-                            current = current_a;
-                            current_a = (*current_a.unwrap().as_ptr()).next;
-                        } else {
-                            current = current_b;
-                            current_b = (*current_b.unwrap().as_ptr()).next;
+                    tail = Some(run_tail);
+                    merges += 1;
+                }
+            }
+            self.start = head;
+            self.end = tail;
+            if merges <= 1 {
+                break;
+            }
+            width *= 2;
+        }
+    }
+
+    // Removes consecutive equal elements, keeping the first of each group;
+    // on a sorted list this leaves only distinct values.
+    pub fn dedup_sorted(&mut self) {
+        let mut current = self.start;
+        while let Some(node) = current {
+            // SAFETY: `next` is a live node following `node`; once unlinked it is freed exactly once.
+            unsafe {
+                match (*node.as_ptr()).next {
+                    Some(next) if (*next.as_ptr()).val == (*node.as_ptr()).val => {
+                        (*node.as_ptr()).next = (*next.as_ptr()).next;
+                        if self.end == Some(next) {
+                            self.end = Some(node);
                         }
+                        self.length -= 1;
+                        drop(Box::from_raw(next.as_ptr()));
+                    }
+                    next => current = next,
+                }
+            }
+        }
+    }
+
+    // Cuts the chain after at most `count` nodes starting at `run` and returns the remainder.
+    unsafe fn split_after(run: NonNull<Node<T>>, count: usize) -> Option<NonNull<Node<T>>> {
+        let mut last = run;
+        for _ in 1..count {
+            match (*last.as_ptr()).next {
+                Some(next) => last = next,
+                None => return None,
+            }
+        }
+        (*last.as_ptr()).next.take()
+    }
+
+    // Merges two detached sorted chains and returns the head and tail of the result;
+    // on ties the node from `left` goes first.
+    unsafe fn merge_runs<F>(
+        left: NonNull<Node<T>>,
+        right: Option<NonNull<Node<T>>>,
+        compare: &mut F,
+    ) -> (NonNull<Node<T>>, NonNull<Node<T>>)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mut left = Some(left);
+        let mut right = right;
+        let mut head: Option<NonNull<Node<T>>> = None;
+        let mut tail: Option<NonNull<Node<T>>> = None;
+        loop {
+            let node = match (left, right) {
+                (Some(l), Some(r)) => {
+                    if compare(&(*r.as_ptr()).val, &(*l.as_ptr()).val) == Ordering::Less {
+                        right = (*r.as_ptr()).next;
+                        r
+                    } else {
+                        left = (*l.as_ptr()).next;
+                        l
                     }
                 }
-                unsafe {
-                    (*temp.unwrap().as_ptr()).next = current;
-                    temp = current;
+                // one side is exhausted: the other is already a sorted chain, append it whole
+                (Some(rest), None) | (None, Some(rest)) => {
+                    let mut last = rest;
+                    while let Some(next) = (*last.as_ptr()).next {
+                        last = next;
+                    }
+                    match tail {
+                        None => head = Some(rest),
+                        Some(tail) => (*tail.as_ptr()).next = Some(rest),
+                    }
+                    tail = Some(last);
+                    break;
                 }
+                (None, None) => break,
+            };
+            match tail {
+                None => head = Some(node),
+                Some(tail) => (*tail.as_ptr()).next = Some(node),
             }
-            let end = current;
-            (start, end)
-        };
-        Self { length, start, end }
+            tail = Some(node);
+        }
+        (
+            head.expect("`left` is never empty"),
+            tail.expect("`left` is never empty"),
+        )
+    }
+
+    fn alloc_node(obj: T) -> NonNull<Node<T>> {
+        NonNull::from(Box::leak(Box::new(Node::new(obj))))
+    }
+
+    fn node_at(&self, index: usize) -> Option<NonNull<Node<T>>> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut current = self.start;
+        for _ in 0..index {
+            // SAFETY: index < length, so the walk never runs past the last node.
+            current = unsafe { (*current?.as_ptr()).next };
+        }
+        current
+    }
+
+    // Detaches the first node without freeing it; the caller takes over its ownership.
+    fn unlink_front(&mut self) -> Option<NonNull<Node<T>>> {
+        let node = self.start?;
+        // SAFETY: `node` is the live head of this list.
+        unsafe {
+            self.start = (*node.as_ptr()).next.take();
+        }
+        if self.start.is_none() {
+            self.end = None;
+        }
+        self.length -= 1;
+        Some(node)
+    }
+
+    // Appends a node that is not reachable from any list, taking over its ownership.
+    fn link_back(&mut self, node: NonNull<Node<T>>) {
+        // SAFETY: `node` is detached and `end`, if any, is the live tail of this list.
+        unsafe {
+            (*node.as_ptr()).next = None;
+            match self.end {
+                None => self.start = Some(node),
+                Some(end_ptr) => (*end_ptr.as_ptr()).next = Some(node),
+            }
+        }
+        self.end = Some(node);
+        self.length += 1;
     }
 }
 
-impl<T: Ord> Display for LinkedList<T>
-where
-    T: Display,
-{
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match self.start {
-            Some(node) => write!(f, "{}", unsafe { node.as_ref() }),
-            None => Ok(()),
+// Head of one input list in `merge_all`; ordered so that `BinaryHeap` (a max-heap) pops the
+// smallest value first and, among equal values, the one from the earliest list.
+struct HeapEntry<T> {
+    node: NonNull<Node<T>>,
+    source: usize,
+}
+
+impl<T: Ord> HeapEntry<T> {
+    fn val(&self) -> &T {
+        // SAFETY: an entry only lives in the heap while its node is the head of its list.
+        unsafe { &(*self.node.as_ptr()).val }
+    }
+}
+
+impl<T: Ord> Ord for HeapEntry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .val()
+            .cmp(self.val())
+            .then_with(|| other.source.cmp(&self.source))
+    }
+}
+
+impl<T: Ord> PartialOrd for HeapEntry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: Ord> PartialEq for HeapEntry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: Ord> Eq for HeapEntry<T> {}
+
+impl<T: Ord> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        // iterative, so dropping a long list cannot overflow the stack
+        while self.pop_front().is_some() {}
+    }
+}
+
+pub struct Iter<'a, T> {
+    next: Option<NonNull<Node<T>>>,
+    remaining: usize,
+    marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let node = self.next?;
+        self.remaining -= 1;
+        // SAFETY: the list is borrowed for `'a`, so the node outlives the returned reference.
+        unsafe {
+            self.next = (*node.as_ptr()).next;
+            Some(&(*node.as_ptr()).val)
         }
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
 }
 
-impl<T> Display for Node<T>
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    next: Option<NonNull<Node<T>>>,
+    remaining: usize,
+    marker: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        let node = self.next?;
+        self.remaining -= 1;
+        // SAFETY: the list is mutably borrowed for `'a` and each node is yielded only once.
+        unsafe {
+            self.next = (*node.as_ptr()).next;
+            Some(&mut (*node.as_ptr()).val)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+pub struct IntoIter<T: Ord>(LinkedList<T>);
+
+impl<T: Ord> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
+
+impl<T: Ord> ExactSizeIterator for IntoIter<T> {}
+
+impl<T: Ord> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T: Ord> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T: Ord> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T: Ord> Extend<T> for LinkedList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for obj in iter {
+            self.add(obj);
+        }
+    }
+}
+
+impl<T: Ord> Display for LinkedList<T>
 where
     T: Display,
 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match self.next {
-            Some(node) => write!(f, "{}, {}", self.val, unsafe { node.as_ref() }),
-            None => write!(f, "{}", self.val),
+        for (i, val) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", val)?;
         }
+        Ok(())
     }
 }
 
@@ -183,7 +602,7 @@ mod tests {
             list_b.add(vec_b[i]);
         }
         println!("list a {} list b {}", list_a, list_b);
-        let mut list_c = LinkedList::<i32>::merge(list_a, list_b);
+        let list_c = LinkedList::<i32>::merge(list_a, list_b);
         println!("merged List is {}", list_c);
         for i in 0..target_vec.len() {
             assert_eq!(target_vec[i], *list_c.get(i as i32).unwrap());
@@ -203,10 +622,129 @@ mod tests {
         for i in 0..vec_b.len() {
             list_b.add(vec_b[i]);
         }
-        let mut list_c = LinkedList::<i32>::merge(list_a, list_b);
+        let list_c = LinkedList::<i32>::merge(list_a, list_b);
         println!("merged List is {}", list_c);
         for i in 0..target_vec.len() {
             assert_eq!(target_vec[i], *list_c.get(i as i32).unwrap());
         }
     }
+
+    #[test]
+    fn test_push_pop_front() {
+        let mut list = LinkedList::<i32>::new();
+        list.push_front(2);
+        list.push_front(1);
+        list.add(3);
+        assert_eq!(3, list.len());
+        assert_eq!(Some(1), list.pop_front());
+        assert_eq!(Some(2), list.pop_front());
+        assert_eq!(Some(3), list.pop_front());
+        assert_eq!(None, list.pop_front());
+        assert!(list.is_empty());
+        list.add(4);
+        assert_eq!(Some(&4), list.get(0));
+    }
+
+    #[test]
+    fn test_insert_remove_at() {
+        let mut list: LinkedList<i32> = vec![1, 3].into_iter().collect();
+        list.insert_at(1, 2);
+        list.insert_at(0, 0);
+        list.insert_at(4, 4);
+        assert_eq!(vec![0, 1, 2, 3, 4], list.iter().copied().collect::<Vec<_>>());
+        assert_eq!(Some(4), list.remove_at(4));
+        assert_eq!(Some(2), list.remove_at(2));
+        assert_eq!(None, list.remove_at(3));
+        list.add(5);
+        *list.get_mut(3).unwrap() += 1;
+        assert_eq!("0, 1, 3, 6", list.to_string());
+        assert_eq!(None, list.get(-1));
+    }
+
+    #[test]
+    fn test_reverse_and_iterators() {
+        let mut list: LinkedList<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+        list.reverse();
+        for val in &mut list {
+            val.push('!');
+        }
+        list.extend(vec!["d".to_string()]);
+        assert_eq!(4, list.iter().len());
+        assert_eq!(Some(&"a!".to_string()), list.get(2));
+        let items: Vec<String> = list.into_iter().collect();
+        assert_eq!(vec!["c!", "b!", "a!", "d"], items);
+    }
+
+    #[test]
+    fn test_long_list_get_and_drop() {
+        let list: LinkedList<u32> = (0..200_000).collect();
+        assert_eq!(Some(&199_999), list.get(199_999));
+        assert_eq!(None, list.get(200_000));
+        let mut partial = list.into_iter();
+        assert_eq!(Some(0), partial.next());
+    }
+
+    // pseudo-random numbers without external crates
+    fn lcg(seed: &mut u64) -> u64 {
+        *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        *seed >> 33
+    }
+
+    #[test]
+    fn test_merge_all() {
+        let lists = vec![
+            vec![(1, 'a'), (4, 'a'), (7, 'a')],
+            vec![],
+            vec![(1, 'c'), (2, 'c'), (9, 'c')],
+            vec![(0, 'd'), (4, 'd')],
+        ];
+        let lists: Vec<LinkedList<(i32, char)>> =
+            lists.into_iter().map(|v| v.into_iter().collect()).collect();
+        let merged = LinkedList::merge_all(lists);
+        assert_eq!(8, merged.len());
+        let items: Vec<_> = merged.into_iter().collect();
+        assert_eq!(
+            vec![(0, 'd'), (1, 'a'), (1, 'c'), (2, 'c'), (4, 'a'), (4, 'd'), (7, 'a'), (9, 'c')],
+            items
+        );
+        assert!(LinkedList::<i32>::merge_all(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn test_sort_matches_vec_sort() {
+        let mut seed = 42;
+        for len in [0, 1, 2, 3, 7, 64, 1000, 4097] {
+            let values: Vec<u64> = (0..len).map(|_| lcg(&mut seed) % 100).collect();
+            let mut list: LinkedList<u64> = values.iter().copied().collect();
+            list.sort();
+            let mut expected = values.clone();
+            expected.sort();
+            assert_eq!(expected, list.iter().copied().collect::<Vec<_>>());
+            assert_eq!(len, list.len());
+            // `end` must still point at the last node after relinking
+            list.add(u64::MAX);
+            assert_eq!(Some(&u64::MAX), list.get(len as i32));
+        }
+    }
+
+    #[test]
+    fn test_sort_by_is_stable() {
+        let mut seed = 7;
+        let values: Vec<(u64, usize)> = (0..500).map(|i| (lcg(&mut seed) % 10, i)).collect();
+        let mut list: LinkedList<(u64, usize)> = values.iter().copied().collect();
+        list.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut expected = values.clone();
+        expected.sort_by_key(|v| v.0);
+        assert_eq!(expected, list.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_dedup_sorted() {
+        let mut list: LinkedList<i32> = vec![1, 1, 2, 3, 3, 3, 5, 5].into_iter().collect();
+        list.dedup_sorted();
+        assert_eq!("1, 2, 3, 5", list.to_string());
+        assert_eq!(4, list.len());
+        list.add(6);
+        assert_eq!(Some(&6), list.get(4));
+    }
 }
@@ -3,7 +3,12 @@
     This problem requires you to reverse a doubly linked list
 */
 
+use std::borrow::Borrow;
+use std::collections::HashMap;
 use std::fmt::{self, Display, Formatter};
+use std::hash::Hash;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
 use std::mem;
 use std::ptr::NonNull;
 
@@ -23,6 +28,14 @@ impl<T> Node<T> {
         }
     }
 }
+
+// Safety invariants upheld by every method of `LinkedList` and `CursorMut`:
+// - every node reachable from `start` was allocated with `Box::into_raw` and is owned by
+//   exactly one list; it is freed exactly once, by a pop/remove method or by `Drop`;
+// - `start`/`end` are both `None` for an empty list, otherwise they point to the first and
+//   last node, whose `prev`/`next` respectively are `None`;
+// - for adjacent nodes `a.next == Some(b)` exactly when `b.prev == Some(a)`;
+// - `length` equals the number of nodes reachable from `start`.
 #[derive(Debug)]
 struct LinkedList<T> {
     length: u32,
@@ -45,69 +58,692 @@ impl<T> LinkedList<T> {
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.length as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
     pub fn add(&mut self, obj: T) {
-        let mut node = Box::new(Node::new(obj));
-        node.next = None;
-        node.prev = self.end;
-        let node_ptr = Some(unsafe { NonNull::new_unchecked(Box::into_raw(node)) });
-        match self.end {
-            None => self.start = node_ptr,
-            Some(end_ptr) => unsafe { (*end_ptr.as_ptr()).next = node_ptr },
-        }
-        self.end = node_ptr;
-        self.length += 1;
+        self.push_back(obj);
+    }
+
+    pub fn push_back(&mut self, obj: T) {
+        // SAFETY: the new node goes after the current tail.
+        unsafe { self.link_between(Self::alloc_node(obj), self.end, None) };
+    }
+
+    pub fn push_front(&mut self, obj: T) {
+        // SAFETY: the new node goes before the current head.
+        unsafe { self.link_between(Self::alloc_node(obj), None, self.start) };
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        // SAFETY: `start` is a node of this list; once unlinked it is freed exactly once.
+        self.start.map(|node| unsafe {
+            self.unlink_node(node);
+            Box::from_raw(node.as_ptr()).val
+        })
     }
 
-    pub fn get(&mut self, index: i32) -> Option<&T> {
-        self.get_ith_node(self.start, index)
+    pub fn pop_back(&mut self) -> Option<T> {
+        // SAFETY: `end` is a node of this list; once unlinked it is freed exactly once.
+        self.end.map(|node| unsafe {
+            self.unlink_node(node);
+            Box::from_raw(node.as_ptr()).val
+        })
     }
 
-    fn get_ith_node(&mut self, node: Option<NonNull<Node<T>>>, index: i32) -> Option<&T> {
-        match node {
-            None => None,
-            Some(next_ptr) => match index {
-                0 => Some(unsafe { &(*next_ptr.as_ptr()).val }),
-                _ => self.get_ith_node(unsafe { (*next_ptr.as_ptr()).next }, index - 1),
-            },
+    pub fn get(&self, index: i32) -> Option<&T> {
+        if index < 0 || index as usize >= self.len() {
+            return None;
         }
+        self.iter().nth(index as usize)
     }
+
     pub fn reverse(&mut self) {
         let mut current = self.start;
-        self.start = self.end;
-        while let Some(next_ptr) = current {
+        while let Some(node) = current {
+            // SAFETY: every node is visited once and only its own links are swapped.
             unsafe {
-                mem::swap(
-                    &mut (*next_ptr.as_ptr()).next,
-                    &mut (*next_ptr.as_ptr()).prev,
-                )
-            };
-            current = unsafe { (*next_ptr.as_ptr()).prev };
+                let node = &mut *node.as_ptr();
+                mem::swap(&mut node.next, &mut node.prev);
+                current = node.prev;
+            }
+        }
+        mem::swap(&mut self.start, &mut self.end);
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            front: self.start,
+            back: self.end,
+            remaining: self.len(),
+            marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            front: self.start,
+            back: self.end,
+            remaining: self.len(),
+            marker: PhantomData,
+        }
+    }
+
+    // Cursor on the first element; on an empty list it starts on the "ghost" position
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            current: self.start,
+            index: 0,
+            list: self,
+        }
+    }
+
+    // Cursor on the last element; on an empty list it starts on the "ghost" position
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        let index = self.len().saturating_sub(1);
+        CursorMut {
+            current: self.end,
+            index,
+            list: self,
         }
-        self.end = current;
+    }
+
+    // Moves a node of this list to the front without reallocating it.
+    unsafe fn move_to_front(&mut self, node: NonNull<Node<T>>) {
+        if self.start != Some(node) {
+            self.unlink_node(node);
+            self.link_between(node, None, self.start);
+        }
+    }
+
+    fn alloc_node(obj: T) -> NonNull<Node<T>> {
+        NonNull::from(Box::leak(Box::new(Node::new(obj))))
+    }
+
+    // Links a detached node between `prev` and `next`, which must be adjacent in this list
+    // (`None` standing for the ends).
+    unsafe fn link_between(
+        &mut self,
+        node: NonNull<Node<T>>,
+        prev: Option<NonNull<Node<T>>>,
+        next: Option<NonNull<Node<T>>>,
+    ) {
+        (*node.as_ptr()).prev = prev;
+        (*node.as_ptr()).next = next;
+        match prev {
+            Some(prev) => (*prev.as_ptr()).next = Some(node),
+            None => self.start = Some(node),
+        }
+        match next {
+            Some(next) => (*next.as_ptr()).prev = Some(node),
+            None => self.end = Some(node),
+        }
+        self.length += 1;
+    }
+
+    // Detaches a node of this list without freeing it; the caller takes over its ownership.
+    unsafe fn unlink_node(&mut self, node: NonNull<Node<T>>) {
+        let node = &mut *node.as_ptr();
+        match node.prev {
+            Some(prev) => (*prev.as_ptr()).next = node.next,
+            None => self.start = node.next,
+        }
+        match node.next {
+            Some(next) => (*next.as_ptr()).prev = node.prev,
+            None => self.end = node.prev,
+        }
+        node.prev = None;
+        node.next = None;
+        self.length -= 1;
+    }
+
+    // Moves all nodes of `other` between the adjacent positions `prev` and `next`.
+    unsafe fn splice_nodes(
+        &mut self,
+        prev: Option<NonNull<Node<T>>>,
+        next: Option<NonNull<Node<T>>>,
+        mut other: LinkedList<T>,
+    ) {
+        let (first, last) = match (other.start.take(), other.end.take()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return,
+        };
+        (*first.as_ptr()).prev = prev;
+        (*last.as_ptr()).next = next;
+        match prev {
+            Some(prev) => (*prev.as_ptr()).next = Some(first),
+            None => self.start = Some(first),
+        }
+        match next {
+            Some(next) => (*next.as_ptr()).prev = Some(last),
+            None => self.end = Some(last),
+        }
+        self.length += mem::replace(&mut other.length, 0);
+    }
+
+    // Splits off everything after `node`, which is the `kept`-th node (1-based) of this list;
+    // `None` splits off the whole list.
+    unsafe fn split_after_node(&mut self, node: Option<NonNull<Node<T>>>, kept: usize) -> Self {
+        let node = match node {
+            Some(node) => node,
+            None => return mem::take(self),
+        };
+        let first = match (*node.as_ptr()).next.take() {
+            Some(first) => first,
+            None => return Self::new(),
+        };
+        (*first.as_ptr()).prev = None;
+        let second = Self {
+            length: self.length - kept as u32,
+            start: Some(first),
+            end: self.end,
+        };
+        self.end = Some(node);
+        self.length = kept as u32;
+        second
+    }
+
+    // Splits off everything before `node`, which has `before` nodes in front of it;
+    // `None` splits off the whole list.
+    unsafe fn split_before_node(&mut self, node: Option<NonNull<Node<T>>>, before: usize) -> Self {
+        let node = match node {
+            Some(node) => node,
+            None => return mem::take(self),
+        };
+        let last = match (*node.as_ptr()).prev.take() {
+            Some(last) => last,
+            None => return Self::new(),
+        };
+        (*last.as_ptr()).next = None;
+        let first = Self {
+            length: before as u32,
+            start: self.start,
+            end: Some(last),
+        };
+        self.start = Some(node);
+        self.length -= before as u32;
+        first
     }
 }
 
-impl<T> Display for LinkedList<T>
-where
-    T: Display,
-{
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match self.start {
-            Some(node) => write!(f, "{}", unsafe { node.as_ref() }),
-            None => Ok(()),
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        // iterative, so dropping a long list cannot overflow the stack
+        while self.pop_front().is_some() {}
+    }
+}
+
+// Least-recently-used cache: entries live in the doubly linked list ordered from most to least
+// recently used, and the hash index maps each key to its node, so lookups, promotions and
+// evictions are all O(1).
+struct LruCache<K, V> {
+    capacity: usize,
+    list: LinkedList<(K, V)>,
+    index: HashMap<K, NonNull<Node<(K, V)>>>,
+    on_evict: Option<Box<dyn FnMut(K, V)>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    // A cache holding at most `capacity` entries; with capacity 0 every `put` is evicted at once
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            list: LinkedList::new(),
+            index: HashMap::new(),
+            on_evict: None,
+        }
+    }
+
+    // Called with every entry dropped to respect the capacity (by `put` or `resize`);
+    // entries taken out with `remove` or replaced by `put` are not reported
+    pub fn on_evict<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(K, V) + 'static,
+    {
+        self.on_evict = Some(Box::new(callback));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.index.contains_key(key)
+    }
+
+    // Looks up a key and marks it as most recently used
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let node = *self.index.get(key)?;
+        // SAFETY: indexed nodes belong to `self.list` and live until they are removed from both.
+        unsafe {
+            self.list.move_to_front(node);
+            Some(&(*node.as_ptr()).val.1)
+        }
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let node = *self.index.get(key)?;
+        // SAFETY: as in `get`; `&mut self` guarantees exclusive access to the value.
+        unsafe {
+            self.list.move_to_front(node);
+            Some(&mut (*node.as_ptr()).val.1)
+        }
+    }
+
+    // Looks up a key without changing its position
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let node = *self.index.get(key)?;
+        // SAFETY: as in `get`.
+        unsafe { Some(&(*node.as_ptr()).val.1) }
+    }
+
+    // Inserts or updates an entry and marks it as most recently used, evicting the least
+    // recently used entries beyond the capacity; returns the previous value of the key
+    pub fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&node) = self.index.get(&key) {
+            // SAFETY: as in `get`.
+            unsafe {
+                self.list.move_to_front(node);
+                return Some(mem::replace(&mut (*node.as_ptr()).val.1, value));
+            }
+        }
+        self.list.push_front((key.clone(), value));
+        let node = self.list.start.expect("an entry was just pushed");
+        self.index.insert(key, node);
+        self.evict_to(self.capacity);
+        None
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let node = self.index.remove(key)?;
+        // SAFETY: the node belonged to `self.list` and is no longer indexed, so it is freed once.
+        unsafe {
+            self.list.unlink_node(node);
+            Some(Box::from_raw(node.as_ptr()).val.1)
+        }
+    }
+
+    // Changes the capacity, evicting the least recently used entries if it shrinks
+    pub fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict_to(capacity);
+    }
+
+    // Keys from most to least recently used
+    pub fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.list.iter().map(|(key, _)| key)
+    }
+
+    fn evict_to(&mut self, capacity: usize) {
+        while self.list.len() > capacity {
+            let (key, value) = self.list.pop_back().expect("the list is not empty");
+            self.index.remove(&key);
+            if let Some(callback) = self.on_evict.as_mut() {
+                callback(key, value);
+            }
         }
     }
 }
 
-impl<T> Display for Node<T>
+// A cursor that can walk the list in both directions and edit it in O(1) per operation.
+// Besides the elements there is a "ghost" position between the back and the front, where
+// `current` is `None` and `index` equals the length of the list.
+struct CursorMut<'a, T> {
+    current: Option<NonNull<Node<T>>>,
+    index: usize,
+    list: &'a mut LinkedList<T>,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    // Index of the current element, `None` on the ghost position
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        // SAFETY: the node belongs to the mutably borrowed list.
+        self.current
+            .map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        let next = match self.current {
+            // SAFETY: the current node belongs to the list.
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.start,
+        };
+        // SAFETY: see above.
+        next.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        let prev = match self.current {
+            // SAFETY: the current node belongs to the list.
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.end,
+        };
+        // SAFETY: see above.
+        prev.map(|node| unsafe { &mut (*node.as_ptr()).val })
+    }
+
+    // Moves to the next element; from the back it moves to the ghost position, from the
+    // ghost position to the front.
+    pub fn move_next(&mut self) {
+        match self.current {
+            Some(node) => {
+                // SAFETY: the current node belongs to the list.
+                self.current = unsafe { (*node.as_ptr()).next };
+                self.index += 1;
+            }
+            None => {
+                self.current = self.list.start;
+                self.index = 0;
+            }
+        }
+    }
+
+    // Moves to the previous element; from the front it moves to the ghost position, from the
+    // ghost position to the back.
+    pub fn move_prev(&mut self) {
+        match self.current {
+            Some(node) => {
+                // SAFETY: the current node belongs to the list.
+                self.current = unsafe { (*node.as_ptr()).prev };
+                self.index = match self.current {
+                    Some(_) => self.index - 1,
+                    None => self.list.len(),
+                };
+            }
+            None => {
+                self.current = self.list.end;
+                self.index = self.list.len().saturating_sub(1);
+            }
+        }
+    }
+
+    // Inserts before the current element; on the ghost position that is the back of the list
+    pub fn insert_before(&mut self, obj: T) {
+        let prev = match self.current {
+            // SAFETY: the current node belongs to the list.
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.end,
+        };
+        // SAFETY: `prev` and `current` are adjacent positions of the list.
+        unsafe {
+            self.list
+                .link_between(LinkedList::alloc_node(obj), prev, self.current)
+        };
+        self.index += 1;
+    }
+
+    // Inserts after the current element; on the ghost position that is the front of the list
+    pub fn insert_after(&mut self, obj: T) {
+        let next = match self.current {
+            // SAFETY: the current node belongs to the list.
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.start,
+        };
+        // SAFETY: `current` and `next` are adjacent positions of the list.
+        unsafe {
+            self.list
+                .link_between(LinkedList::alloc_node(obj), self.current, next)
+        };
+        if self.current.is_none() {
+            self.index += 1;
+        }
+    }
+
+    // Removes the current element and moves to the next one
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+        // SAFETY: the current node belongs to the list; once unlinked it is freed exactly once.
+        unsafe {
+            self.current = (*node.as_ptr()).next;
+            self.list.unlink_node(node);
+            Some(Box::from_raw(node.as_ptr()).val)
+        }
+    }
+
+    // Splits off everything after the current element; on the ghost position the whole list
+    pub fn split_after(&mut self) -> LinkedList<T> {
+        // SAFETY: `current` is the `index + 1`-th node of the list.
+        let split = unsafe { self.list.split_after_node(self.current, self.index + 1) };
+        if self.current.is_none() {
+            self.index = 0;
+        }
+        split
+    }
+
+    // Splits off everything before the current element; on the ghost position the whole list
+    pub fn split_before(&mut self) -> LinkedList<T> {
+        // SAFETY: `current` has `index` nodes in front of it.
+        let split = unsafe { self.list.split_before_node(self.current, self.index) };
+        self.index = 0;
+        split
+    }
+
+    // Moves all elements of `other` in after the current element; on the ghost position they
+    // go to the front.
+    pub fn splice_after(&mut self, other: LinkedList<T>) {
+        let added = other.len();
+        let next = match self.current {
+            // SAFETY: the current node belongs to the list.
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.start,
+        };
+        // SAFETY: `current` and `next` are adjacent positions of the list.
+        unsafe { self.list.splice_nodes(self.current, next, other) };
+        if self.current.is_none() {
+            self.index += added;
+        }
+    }
+
+    // Moves all elements of `other` in before the current element; on the ghost position they
+    // go to the back.
+    pub fn splice_before(&mut self, other: LinkedList<T>) {
+        let added = other.len();
+        let prev = match self.current {
+            // SAFETY: the current node belongs to the list.
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.end,
+        };
+        // SAFETY: `prev` and `current` are adjacent positions of the list.
+        unsafe { self.list.splice_nodes(prev, self.current, other) };
+        self.index += added;
+    }
+}
+
+pub struct Iter<'a, T> {
+    front: Option<NonNull<Node<T>>>,
+    back: Option<NonNull<Node<T>>>,
+    remaining: usize,
+    marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        // SAFETY: `remaining` keeps the two ends from crossing, and the list is borrowed for `'a`.
+        self.front.map(|node| unsafe {
+            self.front = (*node.as_ptr()).next;
+            &(*node.as_ptr()).val
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        // SAFETY: as in `next`.
+        self.back.map(|node| unsafe {
+            self.back = (*node.as_ptr()).prev;
+            &(*node.as_ptr()).val
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+pub struct IterMut<'a, T> {
+    front: Option<NonNull<Node<T>>>,
+    back: Option<NonNull<Node<T>>>,
+    remaining: usize,
+    marker: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        // SAFETY: `remaining` keeps the two ends from crossing, so every node is yielded once.
+        self.front.map(|node| unsafe {
+            self.front = (*node.as_ptr()).next;
+            &mut (*node.as_ptr()).val
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        // SAFETY: as in `next`.
+        self.back.map(|node| unsafe {
+            self.back = (*node.as_ptr()).prev;
+            &mut (*node.as_ptr()).val
+        })
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+pub struct IntoIter<T>(LinkedList<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len(), Some(self.0.len()))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = Self::new();
+        for obj in iter {
+            list.push_back(obj);
+        }
+        list
+    }
+}
+
+impl<T> Display for LinkedList<T>
 where
     T: Display,
 {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        match self.next {
-            Some(node) => write!(f, "{}, {}", self.val, unsafe { node.as_ref() }),
-            None => write!(f, "{}", self.val),
+        for (i, val) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", val)?;
         }
+        Ok(())
     }
 }
 
@@ -166,4 +802,147 @@ mod tests {
             assert_eq!(reverse_vec[i], *list.get(i as i32).unwrap());
         }
     }
+
+    fn to_vec(list: &LinkedList<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_reverse_keeps_both_ends() {
+        let mut list: LinkedList<i32> = (1..=4).collect();
+        list.reverse();
+        list.push_back(0);
+        list.push_front(5);
+        assert_eq!(vec![5, 4, 3, 2, 1, 0], to_vec(&list));
+        assert_eq!(Some(0), list.pop_back());
+        assert_eq!(Some(5), list.pop_front());
+        assert_eq!(4, list.len());
+    }
+
+    #[test]
+    fn test_double_ended_iterators() {
+        let mut list: LinkedList<i32> = (1..=5).collect();
+        let mut iter = list.iter();
+        assert_eq!(Some(&1), iter.next());
+        assert_eq!(Some(&5), iter.next_back());
+        assert_eq!(3, iter.len());
+        assert_eq!(vec![&4, &3, &2], iter.rev().collect::<Vec<_>>());
+        for val in list.iter_mut().rev().take(2) {
+            *val *= 10;
+        }
+        assert_eq!(
+            vec![50, 40, 3, 2, 1],
+            list.into_iter().rev().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_cursor_move_and_edit() {
+        let mut list: LinkedList<i32> = (1..=3).collect();
+        let mut cursor = list.cursor_front_mut();
+        assert_eq!(Some(0), cursor.index());
+        cursor.move_next();
+        assert_eq!(Some(&mut 2), cursor.current());
+        cursor.insert_before(10);
+        cursor.insert_after(20);
+        assert_eq!(Some(2), cursor.index());
+        assert_eq!(Some(&mut 10), cursor.peek_prev());
+        assert_eq!(Some(&mut 20), cursor.peek_next());
+        assert_eq!(Some(2), cursor.remove_current());
+        assert_eq!(Some(&mut 20), cursor.current());
+        cursor.move_next();
+        cursor.move_next();
+        // ghost position between back and front
+        assert_eq!(None, cursor.index());
+        assert_eq!(None, cursor.current());
+        cursor.insert_after(0);
+        cursor.insert_before(99);
+        cursor.move_prev();
+        assert_eq!(Some(&mut 99), cursor.current());
+        assert_eq!(Some(5), cursor.index());
+        assert_eq!(vec![0, 1, 10, 20, 3, 99], to_vec(&list));
+
+        let mut cursor = list.cursor_back_mut();
+        while cursor.remove_current().is_some() {
+            cursor.move_prev();
+        }
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_cursor_split_and_splice() {
+        let mut list: LinkedList<i32> = (1..=6).collect();
+        let mut cursor = list.cursor_front_mut();
+        cursor.move_next();
+        cursor.move_next();
+        let tail = cursor.split_after();
+        let head = cursor.split_before();
+        assert_eq!(Some(0), cursor.index());
+        assert_eq!(vec![1, 2], to_vec(&head));
+        assert_eq!(vec![4, 5, 6], to_vec(&tail));
+        cursor.splice_before(head);
+        cursor.splice_after(tail);
+        assert_eq!(Some(2), cursor.index());
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.move_prev();
+        cursor.splice_after((7..=8).collect());
+        cursor.splice_before((9..=9).collect());
+        assert_eq!(vec![7, 8, 1, 2, 3, 4, 5, 6, 9], to_vec(&list));
+        assert_eq!(9, list.len());
+        assert_eq!(Some(&9), list.iter().next_back());
+    }
+
+    #[test]
+    fn test_lru_cache_evicts_least_recently_used() {
+        use super::LruCache;
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&evicted);
+        let mut cache = LruCache::new(2)
+            .on_evict(move |key: String, value: i32| log.borrow_mut().push((key, value)));
+        assert_eq!(None, cache.put("a".to_string(), 1));
+        assert_eq!(None, cache.put("b".to_string(), 2));
+        assert_eq!(Some(&1), cache.get("a"));
+        assert_eq!(None, cache.put("c".to_string(), 3));
+        assert_eq!(vec![("b".to_string(), 2)], *evicted.borrow());
+        assert!(!cache.contains("b"));
+        // peek does not promote "a", so it is evicted next
+        assert_eq!(Some(&1), cache.peek("a"));
+        cache.put("d".to_string(), 4);
+        assert_eq!(None, cache.peek("a"));
+        assert_eq!(vec!["d", "c"], cache.keys().collect::<Vec<_>>());
+        assert_eq!(2, cache.len());
+    }
+
+    #[test]
+    fn test_lru_cache_update_remove_resize() {
+        use super::LruCache;
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let evictions = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&evictions);
+        let mut cache =
+            LruCache::new(3).on_evict(move |_: u32, _: u32| counter.set(counter.get() + 1));
+        for i in 0..3 {
+            cache.put(i, i * 10);
+        }
+        assert_eq!(Some(0), cache.put(0, 1));
+        *cache.get_mut(&1).unwrap() += 5;
+        assert_eq!(vec![&1, &0, &2], cache.keys().collect::<Vec<_>>());
+        assert_eq!(Some(20), cache.remove(&2));
+        assert_eq!(None, cache.remove(&2));
+        assert_eq!(0, evictions.get());
+        cache.resize(1);
+        assert_eq!(1, evictions.get());
+        assert_eq!(Some(&15), cache.peek(&1));
+        assert_eq!(1, cache.capacity());
+        cache.resize(0);
+        cache.put(7, 7);
+        assert!(cache.is_empty());
+        assert_eq!(3, evictions.get());
+    }
 }
@@ -4,18 +4,765 @@
     you can use bubble sorting, insertion sorting, heap sorting, etc.
 */
 
-fn sort<T: Ord + Clone>(array: &mut [T]) {
-    let n = array.len();
-    for i in 0..n {
-        let k = array[i].clone();
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+// None of the sorts below require `Clone`: elements are only ever swapped in place, and the
+// stable ones sort a permutation of indices first and then apply it with swaps.
+
+fn sort<T: Ord>(array: &mut [T]) {
+    merge_sort(array);
+}
+
+// Slices at most this long are finished with insertion sort
+const INSERTION_THRESHOLD: usize = 16;
+
+// ---------- merge sort: stable, O(n log n), O(n) extra indices ----------
+
+pub fn merge_sort<T: Ord>(array: &mut [T]) {
+    merge_sort_by(array, T::cmp);
+}
+
+pub fn merge_sort_by_key<T, K, F>(array: &mut [T], mut key: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    merge_sort_by(array, |a, b| key(a).cmp(&key(b)));
+}
+
+pub fn merge_sort_by<T, F>(array: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if array.len() < 2 {
+        return;
+    }
+    let mut order: Vec<usize> = (0..array.len()).collect();
+    let mut buffer = order.clone();
+    {
+        let array = &*array;
+        merge_sort_indices(&mut order, &mut buffer, &mut |&i: &usize, &j: &usize| {
+            compare(&array[i], &array[j])
+        });
+    }
+    apply_permutation(array, &mut order);
+}
+
+// Stable top-down merge sort of `items` using `buffer` (same length) as scratch space
+fn merge_sort_indices<I, F>(items: &mut [I], buffer: &mut [I], compare: &mut F)
+where
+    I: Copy,
+    F: FnMut(&I, &I) -> Ordering,
+{
+    let len = items.len();
+    if len <= INSERTION_THRESHOLD {
+        insertion_sort_by(items, compare);
+        return;
+    }
+    let mid = len / 2;
+    merge_sort_indices(&mut items[..mid], &mut buffer[..mid], compare);
+    merge_sort_indices(&mut items[mid..], &mut buffer[mid..], compare);
+    merge_runs(items, mid, buffer, compare);
+}
+
+// Merges the sorted runs `items[..mid]` and `items[mid..]`; on ties the left run goes first
+fn merge_runs<I, F>(items: &mut [I], mid: usize, buffer: &mut [I], compare: &mut F)
+where
+    I: Copy,
+    F: FnMut(&I, &I) -> Ordering,
+{
+    if compare(&items[mid - 1], &items[mid]) != Ordering::Greater {
+        return;
+    }
+    let (mut left, mut right) = (0, mid);
+    for slot in buffer.iter_mut().take(items.len()) {
+        let take_left = right == items.len()
+            || (left < mid && compare(&items[right], &items[left]) != Ordering::Less);
+        if take_left {
+            *slot = items[left];
+            left += 1;
+        } else {
+            *slot = items[right];
+            right += 1;
+        }
+    }
+    items.copy_from_slice(&buffer[..items.len()]);
+}
+
+// Rearranges `array` so that position k holds the element that was at `order[k]`;
+// follows each cycle of the permutation with swaps and marks visited slots in `order`
+fn apply_permutation<T>(array: &mut [T], order: &mut [usize]) {
+    for start in 0..order.len() {
+        if order[start] == usize::MAX {
+            continue;
+        }
+        let mut slot = start;
+        loop {
+            let source = order[slot];
+            order[slot] = usize::MAX;
+            if source == start {
+                break;
+            }
+            array.swap(slot, source);
+            slot = source;
+        }
+    }
+}
+
+// Stable insertion sort by adjacent swaps, used for short slices
+fn insertion_sort_by<T, F>(array: &mut [T], compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for i in 1..array.len() {
         let mut j = i;
-        while j > 0 && array[j - 1] > k {
-            array[j] = array[j - 1].clone();
+        while j > 0 && compare(&array[j - 1], &array[j]) == Ordering::Greater {
+            array.swap(j - 1, j);
             j -= 1;
         }
-        array[j] = k;
     }
 }
+
+// ---------- introsort-style quicksort: unstable, O(n log n) worst case, O(log n) stack ----------
+
+pub fn quick_sort<T: Ord>(array: &mut [T]) {
+    quick_sort_by(array, T::cmp);
+}
+
+pub fn quick_sort_by_key<T, K, F>(array: &mut [T], mut key: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    quick_sort_by(array, |a, b| key(a).cmp(&key(b)));
+}
+
+pub fn quick_sort_by<T, F>(array: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // past 2 * log2(n) levels of partitioning the pivots are clearly bad: switch to heap sort
+    let depth_limit = 2 * (usize::BITS - array.len().leading_zeros()) as usize;
+    introsort(array, depth_limit, &mut compare);
+}
+
+fn introsort<T, F>(mut array: &mut [T], mut depth_limit: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    loop {
+        if array.len() <= INSERTION_THRESHOLD {
+            insertion_sort_by(array, compare);
+            return;
+        }
+        if depth_limit == 0 {
+            heap_sort_by(array, compare);
+            return;
+        }
+        depth_limit -= 1;
+        let (less, greater) = partition(array, compare);
+        // recurse into the smaller side and loop on the larger one to bound the stack depth
+        let greater_len = array.len() - greater;
+        let (left, right) = std::mem::take(&mut array).split_at_mut(greater);
+        let left = &mut left[..less];
+        let (smaller, larger) = if less < greater_len {
+            (left, right)
+        } else {
+            (right, left)
+        };
+        introsort(smaller, depth_limit, compare);
+        array = larger;
+    }
+}
+
+// Three-way partition around a median-of-three pivot; returns `(less, greater)` such that
+// `array[..less]` < pivot, `array[less..greater]` == pivot and `array[greater..]` > pivot
+fn partition<T, F>(array: &mut [T], compare: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = array.len();
+    let (a, b, c) = (0, len / 2, len - 1);
+    let median = if compare(&array[a], &array[b]) == Ordering::Less {
+        if compare(&array[b], &array[c]) == Ordering::Less {
+            b
+        } else if compare(&array[a], &array[c]) == Ordering::Less {
+            c
+        } else {
+            a
+        }
+    } else if compare(&array[a], &array[c]) == Ordering::Less {
+        a
+    } else if compare(&array[b], &array[c]) == Ordering::Less {
+        c
+    } else {
+        b
+    };
+    // the pivot stays at index 0 while the rest is partitioned
+    array.swap(0, median);
+    let (mut lt, mut i, mut gt) = (1, 1, len);
+    while i < gt {
+        match compare(&array[i], &array[0]) {
+            Ordering::Less => {
+                array.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Greater => {
+                gt -= 1;
+                array.swap(i, gt);
+            }
+            Ordering::Equal => i += 1,
+        }
+    }
+    array.swap(0, lt - 1);
+    (lt - 1, gt)
+}
+
+// ---------- heap sort: unstable, O(n log n), O(1) extra space ----------
+
+pub fn heap_sort<T: Ord>(array: &mut [T]) {
+    heap_sort_by(array, T::cmp);
+}
+
+pub fn heap_sort_by_key<T, K, F>(array: &mut [T], mut key: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    heap_sort_by(array, |a, b| key(a).cmp(&key(b)));
+}
+
+pub fn heap_sort_by<T, F>(array: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let len = array.len();
+    for root in (0..len / 2).rev() {
+        sift_down(array, root, len, &mut compare);
+    }
+    for end in (1..len).rev() {
+        array.swap(0, end);
+        sift_down(array, 0, end, &mut compare);
+    }
+}
+
+// Restores the max-heap property of `array[..end]` below `root`
+fn sift_down<T, F>(array: &mut [T], mut root: usize, end: usize, compare: &mut F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    loop {
+        let mut child = 2 * root + 1;
+        if child >= end {
+            return;
+        }
+        if child + 1 < end && compare(&array[child], &array[child + 1]) == Ordering::Less {
+            child += 1;
+        }
+        if compare(&array[root], &array[child]) != Ordering::Less {
+            return;
+        }
+        array.swap(root, child);
+        root = child;
+    }
+}
+
+// ---------- shell sort: unstable, roughly O(n^1.3) with Ciura's gaps, O(1) extra space ----------
+
+pub fn shell_sort<T: Ord>(array: &mut [T]) {
+    shell_sort_by(array, T::cmp);
+}
+
+pub fn shell_sort_by_key<T, K, F>(array: &mut [T], mut key: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    shell_sort_by(array, |a, b| key(a).cmp(&key(b)));
+}
+
+pub fn shell_sort_by<T, F>(array: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    const CIURA_GAPS: [usize; 9] = [1, 4, 10, 23, 57, 132, 301, 701, 1750];
+    let len = array.len();
+    // beyond Ciura's sequence each gap is about 2.25 times the previous one
+    let mut gaps = CIURA_GAPS.to_vec();
+    while let Some(&last) = gaps.last() {
+        let next = last * 9 / 4;
+        if next >= len {
+            break;
+        }
+        gaps.push(next);
+    }
+    for &gap in gaps.iter().rev().filter(|&&gap| gap < len) {
+        for i in gap..len {
+            let mut j = i;
+            while j >= gap && compare(&array[j - gap], &array[j]) == Ordering::Greater {
+                array.swap(j - gap, j);
+                j -= gap;
+            }
+        }
+    }
+}
+
+// ---------- LSD radix sort: stable, O(n * key bytes), integer keys only ----------
+// Radix sort never compares elements, so instead of `sort_by` it takes an integer key.
+
+pub trait RadixKey: Copy {
+    const BYTES: usize;
+
+    // The `i`-th least significant byte of the key, mapped so that byte order matches the
+    // numeric order (signed keys have their sign bit flipped)
+    fn radix_byte(self, i: usize) -> u8;
+}
+
+macro_rules! radix_key_unsigned {
+    ($($ty:ty),*) => {$(
+        impl RadixKey for $ty {
+            const BYTES: usize = std::mem::size_of::<$ty>();
+
+            fn radix_byte(self, i: usize) -> u8 {
+                (self >> (8 * i)) as u8
+            }
+        }
+    )*};
+}
+
+macro_rules! radix_key_signed {
+    ($($ty:ty => $unsigned:ty),*) => {$(
+        impl RadixKey for $ty {
+            const BYTES: usize = std::mem::size_of::<$ty>();
+
+            fn radix_byte(self, i: usize) -> u8 {
+                ((self as $unsigned ^ (1 << (<$unsigned>::BITS - 1))) >> (8 * i)) as u8
+            }
+        }
+    )*};
+}
+
+radix_key_unsigned!(u8, u16, u32, u64, usize);
+radix_key_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize);
+
+pub fn radix_sort<K: RadixKey>(array: &mut [K]) {
+    radix_sort_by_key(array, |&key| key);
+}
+
+pub fn radix_sort_by_key<T, K, F>(array: &mut [T], mut key: F)
+where
+    K: RadixKey,
+    F: FnMut(&T) -> K,
+{
+    if array.len() < 2 {
+        return;
+    }
+    // keys are computed once up front
+    let keys: Vec<K> = array.iter().map(&mut key).collect();
+    let mut order: Vec<usize> = (0..array.len()).collect();
+    let mut buffer = vec![0; array.len()];
+    for byte in 0..K::BYTES {
+        let mut counts = [0usize; 256];
+        for &index in &order {
+            counts[keys[index].radix_byte(byte) as usize] += 1;
+        }
+        // every key has the same byte here: the pass would not move anything
+        if counts.contains(&order.len()) {
+            continue;
+        }
+        let mut offset = 0;
+        for count in counts.iter_mut() {
+            let bucket = *count;
+            *count = offset;
+            offset += bucket;
+        }
+        for &index in &order {
+            let bucket = &mut counts[keys[index].radix_byte(byte) as usize];
+            buffer[*bucket] = index;
+            *bucket += 1;
+        }
+        std::mem::swap(&mut order, &mut buffer);
+    }
+    apply_permutation(array, &mut order);
+}
+
+// ---------- parallel merge sort and quicksort on scoped threads ----------
+// Both split the slice recursively and hand one half to a scoped thread until the pieces are
+// below `PARALLEL_THRESHOLD` or the available cores are used up, then continue sequentially.
+// Every split point depends only on the data, so the output is the same as the sequential
+// sort's: the merge sort stays stable, the quicksort stays unstable.
+
+// Slices shorter than this are not worth a thread
+const PARALLEL_THRESHOLD: usize = 1 << 13;
+
+fn available_threads() -> usize {
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+pub fn par_merge_sort<T: Ord + Sync>(array: &mut [T]) {
+    par_merge_sort_by(array, T::cmp);
+}
+
+pub fn par_merge_sort_by_key<T, K, F>(array: &mut [T], key: F)
+where
+    T: Sync,
+    K: Ord,
+    F: Fn(&T) -> K + Sync,
+{
+    par_merge_sort_by(array, |a, b| key(a).cmp(&key(b)));
+}
+
+pub fn par_merge_sort_by<T, F>(array: &mut [T], compare: F)
+where
+    T: Sync,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    par_merge_sort_with_threads(array, compare, available_threads());
+}
+
+fn par_merge_sort_with_threads<T, F>(array: &mut [T], compare: F, threads: usize)
+where
+    T: Sync,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if array.len() < 2 {
+        return;
+    }
+    let mut order: Vec<usize> = (0..array.len()).collect();
+    let mut buffer = order.clone();
+    {
+        let array = &*array;
+        let compare_indices = |&i: &usize, &j: &usize| compare(&array[i], &array[j]);
+        par_merge_sort_indices(&mut order, &mut buffer, &compare_indices, threads);
+    }
+    apply_permutation(array, &mut order);
+}
+
+fn par_merge_sort_indices<I, F>(items: &mut [I], buffer: &mut [I], compare: &F, threads: usize)
+where
+    I: Copy + Send,
+    F: Fn(&I, &I) -> Ordering + Sync,
+{
+    if items.len() <= PARALLEL_THRESHOLD || threads <= 1 {
+        merge_sort_indices(items, buffer, &mut |a: &I, b: &I| compare(a, b));
+        return;
+    }
+    // same split point as `merge_sort_indices`, so the merges happen in the same order
+    let mid = items.len() / 2;
+    {
+        let (left, right) = items.split_at_mut(mid);
+        let (left_buffer, right_buffer) = buffer.split_at_mut(mid);
+        std::thread::scope(|scope| {
+            scope.spawn(|| par_merge_sort_indices(left, left_buffer, compare, threads / 2));
+            par_merge_sort_indices(right, right_buffer, compare, threads - threads / 2);
+        });
+    }
+    merge_runs(items, mid, buffer, &mut |a: &I, b: &I| compare(a, b));
+}
+
+pub fn par_quick_sort<T: Ord + Send>(array: &mut [T]) {
+    par_quick_sort_by(array, T::cmp);
+}
+
+pub fn par_quick_sort_by_key<T, K, F>(array: &mut [T], key: F)
+where
+    T: Send,
+    K: Ord,
+    F: Fn(&T) -> K + Sync,
+{
+    par_quick_sort_by(array, |a, b| key(a).cmp(&key(b)));
+}
+
+pub fn par_quick_sort_by<T, F>(array: &mut [T], compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    par_quick_sort_with_threads(array, compare, available_threads());
+}
+
+fn par_quick_sort_with_threads<T, F>(array: &mut [T], compare: F, threads: usize)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let depth_limit = 2 * (usize::BITS - array.len().leading_zeros()) as usize;
+    par_introsort(array, depth_limit, &compare, threads);
+}
+
+fn par_introsort<T, F>(array: &mut [T], depth_limit: usize, compare: &F, threads: usize)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if array.len() <= PARALLEL_THRESHOLD || threads <= 1 {
+        introsort(array, depth_limit, &mut |a: &T, b: &T| compare(a, b));
+        return;
+    }
+    if depth_limit == 0 {
+        heap_sort_by(array, compare);
+        return;
+    }
+    // the same partitioning step `introsort` would take, so the result matches `quick_sort`
+    let (less, greater) = partition(array, &mut |a: &T, b: &T| compare(a, b));
+    let (left, right) = array.split_at_mut(greater);
+    let left = &mut left[..less];
+    std::thread::scope(|scope| {
+        scope.spawn(|| par_introsort(left, depth_limit - 1, compare, threads / 2));
+        par_introsort(right, depth_limit - 1, compare, threads - threads / 2);
+    });
+}
+
+// ---------- external merge sort of newline-delimited records ----------
+// Records are read until the memory budget is used up, each chunk is sorted with the stable
+// merge sort and spilled to a temporary run file, and the runs are then combined with a k-way
+// heap merge. Runs are merged in input order and ties go to the earlier run, so the whole sort
+// is stable. Records are lines without their trailing '\n' and are written back with one.
+
+// Runs merged at once; more runs than this are first merged in groups
+const MAX_FAN_IN: usize = 64;
+
+pub struct ExternalSorter<F> {
+    memory_budget: usize,
+    temp_dir: PathBuf,
+    compare: F,
+}
+
+// What an external sort did, mainly to tell whether it had to spill
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct SortStats {
+    pub records: usize,
+    // runs spilled to disk; 0 when the input fit in memory
+    pub runs: usize,
+    // merge passes over the spilled runs
+    pub merge_passes: usize,
+}
+
+impl ExternalSorter<fn(&str, &str) -> Ordering> {
+    // Byte-wise order, a 64 MiB budget and the system temp directory
+    pub fn new() -> Self {
+        Self {
+            memory_budget: 64 << 20,
+            temp_dir: std::env::temp_dir(),
+            compare: str::cmp,
+        }
+    }
+}
+
+impl Default for ExternalSorter<fn(&str, &str) -> Ordering> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> ExternalSorter<F>
+where
+    F: Fn(&str, &str) -> Ordering,
+{
+    // Approximate bytes of records held in memory per run (at least one record per run)
+    pub fn memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget = bytes;
+        self
+    }
+
+    // Directory for the run files; a private subdirectory is created and removed inside it
+    pub fn temp_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.temp_dir = dir.into();
+        self
+    }
+
+    pub fn comparator<G>(self, compare: G) -> ExternalSorter<G>
+    where
+        G: Fn(&str, &str) -> Ordering,
+    {
+        ExternalSorter {
+            memory_budget: self.memory_budget,
+            temp_dir: self.temp_dir,
+            compare,
+        }
+    }
+
+    pub fn sort_file(&self, input: &Path, output: &Path) -> io::Result<SortStats> {
+        let reader = BufReader::new(File::open(input)?);
+        let mut writer = BufWriter::new(File::create(output)?);
+        let stats = self.sort(reader, &mut writer)?;
+        writer.flush()?;
+        Ok(stats)
+    }
+
+    pub fn sort<R: BufRead, W: Write>(&self, mut input: R, mut output: W) -> io::Result<SortStats> {
+        let mut stats = SortStats::default();
+        let mut workspace: Option<RunDir> = None;
+        let mut runs = Vec::new();
+        loop {
+            let (mut chunk, eof) = self.read_chunk(&mut input)?;
+            stats.records += chunk.len();
+            merge_sort_by(&mut chunk, |a, b| (self.compare)(a, b));
+            if eof && runs.is_empty() {
+                // everything fit in memory: no need to touch the disk
+                write_records(&mut output, chunk)?;
+                return Ok(stats);
+            }
+            if !chunk.is_empty() {
+                let dir = match workspace.as_mut() {
+                    Some(dir) => dir,
+                    None => workspace.insert(RunDir::create(&self.temp_dir)?),
+                };
+                let path = dir.next_path();
+                write_records(&mut BufWriter::new(File::create(&path)?), chunk)?;
+                runs.push(path);
+            }
+            if eof {
+                break;
+            }
+        }
+        stats.runs = runs.len();
+        let dir = workspace.as_mut().expect("runs were spilled");
+        while runs.len() > MAX_FAN_IN {
+            let mut merged = Vec::new();
+            for group in runs.chunks(MAX_FAN_IN) {
+                let path = dir.next_path();
+                self.merge_runs(group, BufWriter::new(File::create(&path)?))?;
+                for run in group {
+                    fs::remove_file(run)?;
+                }
+                merged.push(path);
+            }
+            runs = merged;
+            stats.merge_passes += 1;
+        }
+        self.merge_runs(&runs, &mut output)?;
+        stats.merge_passes += 1;
+        Ok(stats)
+    }
+
+    // Reads records until the budget is reached; also reports whether the input is exhausted
+    fn read_chunk<R: BufRead>(&self, input: &mut R) -> io::Result<(Vec<String>, bool)> {
+        let mut chunk = Vec::new();
+        let mut used = 0;
+        loop {
+            let mut line = String::new();
+            if input.read_line(&mut line)? == 0 {
+                return Ok((chunk, true));
+            }
+            if line.ends_with('\n') {
+                line.pop();
+            }
+            used += line.len() + std::mem::size_of::<String>();
+            chunk.push(line);
+            if used >= self.memory_budget {
+                return Ok((chunk, false));
+            }
+        }
+    }
+
+    fn merge_runs<W: Write>(&self, runs: &[PathBuf], mut output: W) -> io::Result<()> {
+        let mut readers = Vec::with_capacity(runs.len());
+        let mut heap = BinaryHeap::with_capacity(runs.len());
+        for (run, path) in runs.iter().enumerate() {
+            let mut reader = BufReader::new(File::open(path)?);
+            if let Some(record) = read_record(&mut reader)? {
+                heap.push(RunHead {
+                    record,
+                    run,
+                    compare: &self.compare,
+                });
+            }
+            readers.push(reader);
+        }
+        while let Some(mut head) = heap.pop() {
+            writeln!(output, "{}", head.record)?;
+            if let Some(record) = read_record(&mut readers[head.run])? {
+                head.record = record;
+                heap.push(head);
+            }
+        }
+        output.flush()
+    }
+}
+
+fn read_record<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    if line.ends_with('\n') {
+        line.pop();
+    }
+    Ok(Some(line))
+}
+
+fn write_records<W: Write>(output: &mut W, records: Vec<String>) -> io::Result<()> {
+    for record in records {
+        writeln!(output, "{}", record)?;
+    }
+    output.flush()
+}
+
+// Current record of one run in the k-way merge; ordered so that `BinaryHeap` (a max-heap) pops
+// the smallest record first and, among equal records, the one from the earliest run
+struct RunHead<'a, F> {
+    record: String,
+    run: usize,
+    compare: &'a F,
+}
+
+impl<F: Fn(&str, &str) -> Ordering> Ord for RunHead<'_, F> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.compare)(&other.record, &self.record).then_with(|| other.run.cmp(&self.run))
+    }
+}
+
+impl<F: Fn(&str, &str) -> Ordering> PartialOrd for RunHead<'_, F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: Fn(&str, &str) -> Ordering> PartialEq for RunHead<'_, F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<F: Fn(&str, &str) -> Ordering> Eq for RunHead<'_, F> {}
+
+// Private directory holding the run files of one sort, removed with everything in it on drop
+struct RunDir {
+    path: PathBuf,
+    next: usize,
+}
+
+impl RunDir {
+    fn create(parent: &Path) -> io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = parent.join(format!(
+            "external-sort-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, AtomicOrdering::SeqCst)
+        ));
+        fs::create_dir_all(&path)?;
+        Ok(Self { path, next: 0 })
+    }
+
+    fn next_path(&mut self) -> PathBuf {
+        self.next += 1;
+        self.path.join(format!("run-{}.txt", self.next))
+    }
+}
+
+impl Drop for RunDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -38,4 +785,243 @@ mod tests {
         sort(&mut vec);
         assert_eq!(vec, vec![11, 22, 33, 44, 55, 66, 77, 88, 99]);
     }
+
+    // pseudo-random numbers without external crates
+    fn random_vec(len: usize, modulo: u32, seed: u64) -> Vec<u32> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (state >> 33) as u32 % modulo
+            })
+            .collect()
+    }
+
+    fn inputs() -> Vec<Vec<u32>> {
+        vec![
+            vec![],
+            vec![1],
+            vec![2, 1],
+            (0..100).collect(),
+            (0..100).rev().collect(),
+            vec![7; 100],
+            random_vec(1000, 10, 1),
+            random_vec(5000, u32::MAX, 2),
+            // organ pipe, a classic bad case for naive quicksort pivots
+            (0..2000).chain((0..2000).rev()).collect(),
+        ]
+    }
+
+    type Record = (u32, usize);
+    type SortFn = fn(&mut [Record]);
+    type Comparator = fn(&u32, &u32) -> std::cmp::Ordering;
+
+    // every sort, applied by key to (key, original position) records
+    const SORTS: &[(&str, bool, SortFn)] = &[
+        ("merge", true, |v| merge_sort_by_key(v, |r| r.0)),
+        ("quick", false, |v| quick_sort_by_key(v, |r| r.0)),
+        ("heap", false, |v| heap_sort_by_key(v, |r| r.0)),
+        ("shell", false, |v| shell_sort_by_key(v, |r| r.0)),
+        ("radix", true, |v| radix_sort_by_key(v, |r| r.0)),
+    ];
+
+    #[test]
+    fn test_all_sorts_sort_by_key() {
+        for input in inputs() {
+            let records: Vec<Record> = input.iter().copied().zip(0..).collect();
+            let mut expected = records.clone();
+            expected.sort_by_key(|r| r.0);
+            for &(name, stable, sort_fn) in SORTS {
+                let mut actual = records.clone();
+                sort_fn(&mut actual);
+                let keys: Vec<u32> = actual.iter().map(|r| r.0).collect();
+                let expected_keys: Vec<u32> = expected.iter().map(|r| r.0).collect();
+                assert_eq!(expected_keys, keys, "{} sort, len {}", name, input.len());
+                if stable {
+                    assert_eq!(expected, actual, "{} sort is not stable", name);
+                }
+                let mut positions: Vec<usize> = actual.iter().map(|r| r.1).collect();
+                positions.sort_unstable();
+                assert!(
+                    positions.iter().copied().eq(0..input.len()),
+                    "{} sort lost elements",
+                    name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_all_sorts_by_ord_and_comparator() {
+        let sorts: &[fn(&mut [u32])] = &[merge_sort, quick_sort, heap_sort, shell_sort, radix_sort];
+        let sorts_by: &[fn(&mut [u32], Comparator)] =
+            &[merge_sort_by, quick_sort_by, heap_sort_by, shell_sort_by];
+        for input in inputs() {
+            let mut expected = input.clone();
+            expected.sort();
+            for sort_fn in sorts {
+                let mut actual = input.clone();
+                sort_fn(&mut actual);
+                assert_eq!(expected, actual);
+            }
+            expected.reverse();
+            for sort_fn in sorts_by {
+                let mut actual = input.clone();
+                sort_fn(&mut actual, |a, b| b.cmp(a));
+                assert_eq!(expected, actual);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sorts_without_clone() {
+        #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+        struct Ticket(i64, String);
+
+        let make = || -> Vec<Ticket> {
+            [5, -3, 0, i64::MIN, 42, -3]
+                .iter()
+                .map(|&n| Ticket(n, n.to_string()))
+                .collect()
+        };
+        let mut expected = make();
+        expected.sort();
+        let sorts: &[fn(&mut [Ticket])] = &[sort, merge_sort, quick_sort, heap_sort, shell_sort];
+        for sort_fn in sorts {
+            let mut actual = make();
+            sort_fn(&mut actual);
+            assert_eq!(expected, actual);
+        }
+        let mut actual = make();
+        radix_sort_by_key(&mut actual, |t| t.0);
+        assert_eq!(expected, actual);
+
+        let mut signed = vec![3i8, -128, 127, -1, 0];
+        radix_sort(&mut signed);
+        assert_eq!(vec![-128, -1, 0, 3, 127], signed);
+    }
+
+    #[test]
+    fn test_parallel_sorts_match_sequential() {
+        // large enough to be split across threads several times
+        let input = random_vec(200_000, 1000, 3);
+        let records: Vec<Record> = input.iter().copied().zip(0..).collect();
+
+        // explicit thread counts, so the threaded path runs even on a single-core machine
+        let mut expected = records.clone();
+        merge_sort_by_key(&mut expected, |r| r.0);
+        for threads in [1, 2, 4, 7] {
+            let mut actual = records.clone();
+            par_merge_sort_with_threads(&mut actual, |a, b| a.0.cmp(&b.0), threads);
+            assert_eq!(expected, actual, "parallel merge sort must stay stable");
+        }
+
+        let mut expected = records.clone();
+        quick_sort_by_key(&mut expected, |r| r.0);
+        for threads in [1, 2, 4, 7] {
+            let mut actual = records.clone();
+            par_quick_sort_with_threads(&mut actual, |a, b| a.0.cmp(&b.0), threads);
+            assert_eq!(expected, actual, "parallel quicksort must be deterministic");
+        }
+        let mut actual = records.clone();
+        par_merge_sort_by_key(&mut actual, |r| r.0);
+        let mut actual = records;
+        par_quick_sort_by_key(&mut actual, |r| r.0);
+
+        let mut sorted = input.clone();
+        sorted.sort();
+        let mut actual = input.clone();
+        par_merge_sort(&mut actual);
+        assert_eq!(sorted, actual);
+        let mut actual = input.clone();
+        par_quick_sort(&mut actual);
+        assert_eq!(sorted, actual);
+        let mut actual = input;
+        par_quick_sort_by(&mut actual, |a, b| b.cmp(a));
+        sorted.reverse();
+        assert_eq!(sorted, actual);
+    }
+
+    // temp directory of a single test, removed at the end of it
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("algorithm3-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_external_sort_spills_and_merges() {
+        let dir = test_dir("external");
+        let lines: Vec<String> = random_vec(10_000, 100_000, 4)
+            .iter()
+            .map(|n| format!("record-{}", n))
+            .collect();
+        let input = dir.join("input.txt");
+        fs::write(&input, lines.join("\n")).unwrap();
+
+        // a tiny budget forces more runs than can be merged at once
+        let sorter = ExternalSorter::new().memory_budget(4 << 10).temp_dir(&dir);
+        let output = dir.join("output.txt");
+        let stats = sorter.sort_file(&input, &output).unwrap();
+        assert_eq!(10_000, stats.records);
+        assert!(stats.runs > MAX_FAN_IN, "only {} runs", stats.runs);
+        assert_eq!(2, stats.merge_passes);
+
+        let mut expected = lines;
+        expected.sort();
+        let actual = fs::read_to_string(&output).unwrap();
+        assert_eq!(expected, actual.lines().collect::<Vec<_>>());
+        // only the input and output are left behind
+        assert_eq!(2, fs::read_dir(&dir).unwrap().count());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_external_sort_comparator_is_stable() {
+        let dir = test_dir("comparator");
+        let input: String = random_vec(2000, 50, 5)
+            .iter()
+            .enumerate()
+            .map(|(seq, key)| format!("{},{}\n", key, seq))
+            .collect();
+        let key = |record: &str| -> u32 { record.split(',').next().unwrap().parse().unwrap() };
+
+        let mut output = Vec::new();
+        let stats = ExternalSorter::new()
+            .memory_budget(1 << 10)
+            .temp_dir(&dir)
+            .comparator(|a: &str, b: &str| key(b).cmp(&key(a)))
+            .sort(input.as_bytes(), &mut output)
+            .unwrap();
+        assert!(stats.runs > 1);
+
+        let mut expected: Vec<&str> = input.lines().collect();
+        expected.sort_by_key(|record| std::cmp::Reverse(key(record)));
+        assert_eq!(
+            expected,
+            String::from_utf8(output)
+                .unwrap()
+                .lines()
+                .collect::<Vec<_>>()
+        );
+
+        // small inputs never touch the disk
+        let mut output = Vec::new();
+        let stats = ExternalSorter::new()
+            .sort("b\na\n".as_bytes(), &mut output)
+            .unwrap();
+        assert_eq!(
+            SortStats {
+                records: 2,
+                runs: 0,
+                merge_passes: 0
+            },
+            stats
+        );
+        assert_eq!(b"a\nb\n".to_vec(), output);
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
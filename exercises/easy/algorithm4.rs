@@ -3,100 +3,1075 @@
     This problem requires you to implement a basic interface for a binary tree
 */
 
+use std::borrow::Borrow;
 use std::cmp::Ordering;
-use std::fmt::Debug;
+use std::collections::VecDeque;
+use std::fmt::{self, Debug};
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
 
-#[derive(Debug)]
-struct TreeNode<T>
-where
-    T: Ord,
-{
-    value: T,
-    left: Option<Box<TreeNode<T>>>,
-    right: Option<Box<TreeNode<T>>>,
-}
+type Link<K, V, M> = Option<Box<TreeNode<K, V, M>>>;
 
-#[derive(Debug)]
-struct BinarySearchTree<T>
-where
-    T: Ord,
-{
-    root: Option<Box<TreeNode<T>>>,
+struct TreeNode<K, V, M> {
+    key: K,
+    value: V,
+    left: Link<K, V, M>,
+    right: Link<K, V, M>,
+    // number of nodes in the subtree rooted here, kept up to date by every strategy
+    size: usize,
+    // balancing data, see `Balance::Meta`
+    meta: M,
 }
 
-impl<T> TreeNode<T>
-where
-    T: Ord,
-{
-    fn new(value: T) -> Self {
+impl<K, V, M> TreeNode<K, V, M> {
+    fn new(key: K, value: V, meta: M) -> Self {
         TreeNode {
+            key,
             value,
             left: None,
             right: None,
+            size: 1,
+            meta,
         }
     }
 }
 
-impl<T> BinarySearchTree<T>
-where
-    T: Ord,
-{
+fn size<K, V, M>(link: &Link<K, V, M>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+fn update_size<K, V, M>(node: &mut TreeNode<K, V, M>) {
+    node.size = 1 + size(&node.left) + size(&node.right);
+}
+
+// How a tree keeps itself balanced: the data stored in every node and the insert/remove
+// algorithms maintaining it. Lookups, traversals and range queries are shared by all of them.
+trait Balance {
+    type Meta;
+
+    // Insert a key, returning the previous value if the key was already present
+    fn insert<K: Ord, V>(root: &mut Link<K, V, Self::Meta>, key: K, value: V) -> Option<V>;
+
+    fn remove<K, V, Q>(root: &mut Link<K, V, Self::Meta>, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized;
+
+    // Check the strategy's own invariants, e.g. height balance or black height
+    fn check<K, V>(root: &Link<K, V, Self::Meta>) -> Result<(), String>;
+}
+
+// Plain binary search tree. Sorted input degrades it to a linked list, so its algorithms,
+// like everything shared below, walk down the tree iteratively rather than recursively.
+struct Unbalanced;
+
+// AVL tree: subtree heights on both sides of every node differ by at most one
+struct Avl;
+
+// Left-leaning red-black tree (Sedgewick): red links lean left, no node has two red links,
+// and every path from the root to a leaf crosses the same number of black links
+struct RedBlack;
+
+struct SearchTree<K, V, B: Balance> {
+    root: Link<K, V, B::Meta>,
+    len: usize,
+    balance: PhantomData<B>,
+}
+
+// Ordered maps sharing one API and differing only in how they stay balanced
+type BstMap<K, V> = SearchTree<K, V, Unbalanced>;
+type AvlMap<K, V> = SearchTree<K, V, Avl>;
+type RbMap<K, V> = SearchTree<K, V, RedBlack>;
+
+impl<K: Ord, V, B: Balance> Default for SearchTree<K, V, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V, B: Balance> SearchTree<K, V, B> {
     fn new() -> Self {
-        BinarySearchTree { root: None }
+        SearchTree {
+            root: None,
+            len: 0,
+            balance: PhantomData,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
-    // Insert a value into the BST
-    fn insert(&mut self, value: T) {
-        match self.root {
-            Some(ref mut root) => {
-                root.as_mut().insert(value);
+    // Insert a key, returning the previous value if the key was already present
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let old = B::insert(&mut self.root, key, value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        find(&self.root, key).map(|node| &node.value)
+    }
+
+    fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut link = &mut self.root;
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Less => link = &mut node.left,
+                Ordering::Greater => link = &mut node.right,
+                Ordering::Equal => return Some(&mut node.value),
             }
-            None => {
-                self.root.replace(Box::new(TreeNode::new(value)));
+        }
+        None
+    }
+
+    fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        find(&self.root, key).is_some()
+    }
+
+    // Remove a key, returning its value
+    fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let removed = B::remove(&mut self.root, key);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    fn min(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    fn max(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    // Greatest entry whose key is <= `key`
+    fn floor<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut best = None;
+        let mut link = self.root.as_deref();
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Less => link = node.left.as_deref(),
+                Ordering::Equal => return Some((&node.key, &node.value)),
+                Ordering::Greater => {
+                    best = Some((&node.key, &node.value));
+                    link = node.right.as_deref();
+                }
             }
         }
+        best
     }
 
-    // Search for a value in the BST
-    fn search(&self, value: T) -> bool {
-        if let Some(ref root) = self.root {
-            root.search(value)
-        } else {
-            false
+    // Smallest entry whose key is >= `key`
+    fn ceiling<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut best = None;
+        let mut link = self.root.as_deref();
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Greater => link = node.right.as_deref(),
+                Ordering::Equal => return Some((&node.key, &node.value)),
+                Ordering::Less => {
+                    best = Some((&node.key, &node.value));
+                    link = node.left.as_deref();
+                }
+            }
+        }
+        best
+    }
+
+    // In-order traversal, i.e. entries sorted by key
+    fn iter(&self) -> Iter<'_, K, V, B::Meta> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            remaining: self.len,
+        };
+        iter.push_left_spine(self.root.as_deref());
+        iter
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &K> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    fn values(&self) -> impl Iterator<Item = &V> + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+
+    // Node, then left subtree, then right subtree
+    fn pre_order(&self) -> PreOrder<'_, K, V, B::Meta> {
+        PreOrder {
+            stack: self.root.as_deref().into_iter().collect(),
+        }
+    }
+
+    // Left subtree, then right subtree, then node
+    fn post_order(&self) -> PostOrder<'_, K, V, B::Meta> {
+        PostOrder {
+            stack: self
+                .root
+                .as_deref()
+                .map(|node| (node, false))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    // Breadth first, level by level from the root
+    fn level_order(&self) -> LevelOrder<'_, K, V, B::Meta> {
+        LevelOrder {
+            queue: self.root.as_deref().into_iter().collect(),
+        }
+    }
+
+    // Entries whose keys fall in `range`, in key order, e.g. `map.range(3..7)`
+    fn range<Q, R>(&self, range: R) -> Range<'_, K, V, B::Meta, Q, R>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let mut stack = Vec::new();
+        // walk down to the first key inside the lower bound, keeping the path of nodes
+        // that are still in range so they are visited after their left subtrees
+        let mut link = self.root.as_deref();
+        while let Some(node) = link {
+            if above_lower(range.start_bound(), node.key.borrow()) {
+                stack.push(node);
+                link = node.left.as_deref();
+            } else {
+                link = node.right.as_deref();
+            }
+        }
+        Range {
+            stack,
+            range,
+            marker: PhantomData,
+        }
+    }
+
+    // Number of keys strictly less than `key`
+    fn rank<Q>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.count_below(key, false)
+    }
+
+    // The entry with the `k`-th smallest key, counting from 0
+    fn select(&self, k: usize) -> Option<(&K, &V)> {
+        let mut k = k;
+        let mut link = self.root.as_deref();
+        while let Some(node) = link {
+            let left = size(&node.left);
+            match k.cmp(&left) {
+                Ordering::Less => link = node.left.as_deref(),
+                Ordering::Equal => return Some((&node.key, &node.value)),
+                Ordering::Greater => {
+                    k -= left + 1;
+                    link = node.right.as_deref();
+                }
+            }
         }
+        None
+    }
+
+    // Number of keys in `range`, e.g. `map.count_range(a..b)`, without visiting them
+    fn count_range<Q, R>(&self, range: R) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let below_start = match range.start_bound() {
+            Bound::Included(start) => self.count_below(start, false),
+            Bound::Excluded(start) => self.count_below(start, true),
+            Bound::Unbounded => 0,
+        };
+        let up_to_end = match range.end_bound() {
+            Bound::Included(end) => self.count_below(end, true),
+            Bound::Excluded(end) => self.count_below(end, false),
+            Bound::Unbounded => self.len,
+        };
+        up_to_end.saturating_sub(below_start)
+    }
+
+    // Number of keys less than `key`, or less than or equal to it when `inclusive`
+    fn count_below<Q>(&self, key: &Q, inclusive: bool) -> usize
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let mut count = 0;
+        let mut link = self.root.as_deref();
+        while let Some(node) = link {
+            match key.cmp(node.key.borrow()) {
+                Ordering::Less => link = node.left.as_deref(),
+                Ordering::Equal => return count + size(&node.left) + usize::from(inclusive),
+                Ordering::Greater => {
+                    count += size(&node.left) + 1;
+                    link = node.right.as_deref();
+                }
+            }
+        }
+        count
+    }
+
+    // Number of levels, 0 for an empty tree
+    fn height(&self) -> usize {
+        let mut height = 0;
+        let mut level: Vec<&TreeNode<K, V, B::Meta>> = self.root.as_deref().into_iter().collect();
+        while !level.is_empty() {
+            height += 1;
+            level = level
+                .iter()
+                .flat_map(|node| {
+                    node.left
+                        .as_deref()
+                        .into_iter()
+                        .chain(node.right.as_deref())
+                })
+                .collect();
+        }
+        height
+    }
+
+    // Debug check of the search-tree order, the cached length and the balancing invariants
+    fn check_invariants(&self) -> Result<(), String> {
+        let nodes = self.pre_order().count();
+        if nodes != self.len {
+            return Err(format!(
+                "len is {} but the tree has {} nodes",
+                self.len, nodes
+            ));
+        }
+        let mut keys = self.pre_order().map(|(key, _)| key).collect::<Vec<_>>();
+        let in_order: Vec<&K> = self.keys().collect();
+        keys.sort();
+        if keys != in_order || in_order.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err("keys are not in strictly increasing in-order".to_string());
+        }
+        let mut stack: Vec<&TreeNode<K, V, B::Meta>> = self.root.as_deref().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            if node.size != 1 + size(&node.left) + size(&node.right) {
+                return Err(format!(
+                    "stored subtree size {} but the subtree has {} nodes",
+                    node.size,
+                    1 + size(&node.left) + size(&node.right)
+                ));
+            }
+            stack.extend(node.left.as_deref());
+            stack.extend(node.right.as_deref());
+        }
+        B::check(&self.root)
     }
 }
 
-impl<T> TreeNode<T>
+fn find<'a, K, V, M, Q>(root: &'a Link<K, V, M>, key: &Q) -> Option<&'a TreeNode<K, V, M>>
 where
-    T: Ord,
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
 {
-    // Insert a node into the tree
-    fn insert(&mut self, value: T) {
-        match value.cmp(&self.value) {
-            Ordering::Less => {
-                if let Some(ref mut left) = self.left {
-                    left.as_mut().insert(value);
-                } else {
-                    self.left.replace(Box::new(TreeNode::new(value)));
-                }
+    let mut link = root.as_deref();
+    while let Some(node) = link {
+        match key.cmp(node.key.borrow()) {
+            Ordering::Less => link = node.left.as_deref(),
+            Ordering::Greater => link = node.right.as_deref(),
+            Ordering::Equal => return Some(node),
+        }
+    }
+    None
+}
+
+fn above_lower<Q: Ord + ?Sized>(bound: Bound<&Q>, key: &Q) -> bool {
+    match bound {
+        Bound::Included(start) => key >= start,
+        Bound::Excluded(start) => key > start,
+        Bound::Unbounded => true,
+    }
+}
+
+fn below_upper<Q: Ord + ?Sized>(bound: Bound<&Q>, key: &Q) -> bool {
+    match bound {
+        Bound::Included(end) => key <= end,
+        Bound::Excluded(end) => key < end,
+        Bound::Unbounded => true,
+    }
+}
+
+//     node              right
+//    /    \             /    \
+//   a    right   =>  node     c
+//        /   \       /  \
+//       b     c     a    b
+fn rotate_left<K, V, M>(mut node: Box<TreeNode<K, V, M>>) -> Box<TreeNode<K, V, M>> {
+    let mut right = node
+        .right
+        .take()
+        .expect("rotating left needs a right child");
+    node.right = right.left.take();
+    update_size(&mut node);
+    right.left = Some(node);
+    update_size(&mut right);
+    right
+}
+
+// mirror image of `rotate_left`
+fn rotate_right<K, V, M>(mut node: Box<TreeNode<K, V, M>>) -> Box<TreeNode<K, V, M>> {
+    let mut left = node.left.take().expect("rotating right needs a left child");
+    node.left = left.right.take();
+    update_size(&mut node);
+    left.right = Some(node);
+    update_size(&mut left);
+    left
+}
+
+impl Balance for Unbalanced {
+    type Meta = ();
+
+    fn insert<K: Ord, V>(root: &mut Link<K, V, ()>, key: K, value: V) -> Option<V> {
+        // only a new key makes the subtrees on its path grow
+        let grows = find(root, &key).is_none();
+        let mut link = root;
+        while let Some(node) = link {
+            let ordering = key.cmp(&node.key);
+            if ordering == Ordering::Equal {
+                return Some(mem::replace(&mut node.value, value));
             }
-            Ordering::Greater => {
-                if let Some(ref mut right) = self.right {
-                    right.as_mut().insert(value);
-                } else {
-                    self.right.replace(Box::new(TreeNode::new(value)));
+            if grows {
+                node.size += 1;
+            }
+            link = match ordering {
+                Ordering::Less => &mut node.left,
+                _ => &mut node.right,
+            };
+        }
+        *link = Some(Box::new(TreeNode::new(key, value, ())));
+        None
+    }
+
+    // a node with two children is replaced by its successor
+    fn remove<K, V, Q>(root: &mut Link<K, V, ()>, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        // every subtree on the path shrinks by one, so make sure the key is there first
+        find(root, key)?;
+        let mut link = root;
+        loop {
+            let node = link.as_mut()?;
+            match key.cmp(node.key.borrow()) {
+                Ordering::Equal => break,
+                ordering => {
+                    node.size -= 1;
+                    link = match ordering {
+                        Ordering::Less => &mut link.as_mut()?.left,
+                        _ => &mut link.as_mut()?.right,
+                    };
                 }
             }
-            _ => {}
         }
+        let mut node = link.take()?;
+        *link = match (node.left.take(), node.right.take()) {
+            (None, child) | (child, None) => child,
+            (Some(left), right) => {
+                let mut right = right;
+                let mut successor = take_min(&mut right)?;
+                successor.left = Some(left);
+                successor.right = right;
+                update_size(&mut successor);
+                Some(successor)
+            }
+        };
+        Some(node.value)
+    }
+
+    fn check<K, V>(_root: &Link<K, V, ()>) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+// Detach the leftmost node of a subtree, moving its right child up into its place
+fn take_min<K, V>(link: &mut Link<K, V, ()>) -> Option<Box<TreeNode<K, V, ()>>> {
+    let mut link = link;
+    while link.as_ref()?.left.is_some() {
+        let node = link.as_mut()?;
+        node.size -= 1;
+        link = &mut node.left;
+    }
+    let mut node = link.take()?;
+    *link = node.right.take();
+    Some(node)
+}
+
+// AVL nodes store the height of their subtree, a leaf has height 1
+impl Balance for Avl {
+    type Meta = usize;
+
+    fn insert<K: Ord, V>(root: &mut Link<K, V, usize>, key: K, value: V) -> Option<V> {
+        let node = match root {
+            Some(node) => node,
+            None => {
+                *root = Some(Box::new(TreeNode::new(key, value, 1)));
+                return None;
+            }
+        };
+        let old = match key.cmp(&node.key) {
+            Ordering::Less => Self::insert(&mut node.left, key, value),
+            Ordering::Greater => Self::insert(&mut node.right, key, value),
+            Ordering::Equal => return Some(mem::replace(&mut node.value, value)),
+        };
+        avl_rebalance(root);
+        old
+    }
+
+    fn remove<K, V, Q>(root: &mut Link<K, V, usize>, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let node = root.as_mut()?;
+        let removed = match key.cmp(node.key.borrow()) {
+            Ordering::Less => Self::remove(&mut node.left, key)?,
+            Ordering::Greater => Self::remove(&mut node.right, key)?,
+            Ordering::Equal => {
+                let mut node = root.take()?;
+                *root = match (node.left.take(), node.right.take()) {
+                    (None, child) | (child, None) => child,
+                    (Some(left), right) => {
+                        let mut right = right;
+                        let mut successor = avl_take_min(&mut right);
+                        successor.left = Some(left);
+                        successor.right = right;
+                        Some(successor)
+                    }
+                };
+                node.value
+            }
+        };
+        avl_rebalance(root);
+        Some(removed)
+    }
+
+    fn check<K, V>(root: &Link<K, V, usize>) -> Result<(), String> {
+        avl_check(root).map(|_| ())
+    }
+}
+
+fn avl_height<K, V>(link: &Link<K, V, usize>) -> usize {
+    link.as_ref().map_or(0, |node| node.meta)
+}
+
+fn avl_update_height<K, V>(node: &mut TreeNode<K, V, usize>) {
+    node.meta = 1 + avl_height(&node.left).max(avl_height(&node.right));
+}
+
+fn avl_rotate_left<K, V>(node: Box<TreeNode<K, V, usize>>) -> Box<TreeNode<K, V, usize>> {
+    let mut top = rotate_left(node);
+    if let Some(left) = top.left.as_mut() {
+        avl_update_height(left);
+    }
+    avl_update_height(&mut top);
+    top
+}
+
+fn avl_rotate_right<K, V>(node: Box<TreeNode<K, V, usize>>) -> Box<TreeNode<K, V, usize>> {
+    let mut top = rotate_right(node);
+    if let Some(right) = top.right.as_mut() {
+        avl_update_height(right);
+    }
+    avl_update_height(&mut top);
+    top
+}
+
+// Refresh the height of the subtree root and rotate if its sides differ by two levels
+fn avl_rebalance<K, V>(link: &mut Link<K, V, usize>) {
+    let mut node = match link.take() {
+        Some(node) => node,
+        None => return,
+    };
+    update_size(&mut node);
+    avl_update_height(&mut node);
+    let left = avl_height(&node.left);
+    let right = avl_height(&node.right);
+    if left > right + 1 {
+        let child = node.left.take().expect("the taller side is not empty");
+        // left-right case: straighten the zig-zag first
+        node.left = Some(if avl_height(&child.right) > avl_height(&child.left) {
+            avl_rotate_left(child)
+        } else {
+            child
+        });
+        node = avl_rotate_right(node);
+    } else if right > left + 1 {
+        let child = node.right.take().expect("the taller side is not empty");
+        node.right = Some(if avl_height(&child.left) > avl_height(&child.right) {
+            avl_rotate_right(child)
+        } else {
+            child
+        });
+        node = avl_rotate_left(node);
+    }
+    *link = Some(node);
+}
+
+// Detach the leftmost node of a non-empty subtree, rebalancing on the way back up
+fn avl_take_min<K, V>(link: &mut Link<K, V, usize>) -> Box<TreeNode<K, V, usize>> {
+    let node = link.as_mut().expect("the subtree is not empty");
+    if node.left.is_some() {
+        let min = avl_take_min(&mut node.left);
+        avl_rebalance(link);
+        return min;
+    }
+    let mut node = link.take().expect("the subtree is not empty");
+    *link = node.right.take();
+    node
+}
+
+// Returns the height of a valid subtree
+fn avl_check<K, V>(link: &Link<K, V, usize>) -> Result<usize, String> {
+    let node = match link {
+        Some(node) => node,
+        None => return Ok(0),
+    };
+    let left = avl_check(&node.left)?;
+    let right = avl_check(&node.right)?;
+    if node.meta != 1 + left.max(right) {
+        return Err(format!(
+            "stored height {} but the subtree is {} high",
+            node.meta,
+            1 + left.max(right)
+        ));
+    }
+    if left.abs_diff(right) > 1 {
+        return Err(format!(
+            "height imbalance: left subtree {} high, right subtree {} high",
+            left, right
+        ));
+    }
+    Ok(node.meta)
+}
+
+// Color of the link from a node's parent to the node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Color {
+    Red,
+    Black,
+}
+
+impl Color {
+    fn flip(self) -> Self {
+        match self {
+            Color::Red => Color::Black,
+            Color::Black => Color::Red,
+        }
+    }
+}
+
+type RbNode<K, V> = Box<TreeNode<K, V, Color>>;
+
+impl Balance for RedBlack {
+    type Meta = Color;
+
+    fn insert<K: Ord, V>(root: &mut Link<K, V, Color>, key: K, value: V) -> Option<V> {
+        let old = rb_insert(root, key, value);
+        if let Some(root) = root.as_mut() {
+            root.meta = Color::Black;
+        }
+        old
+    }
+
+    fn remove<K, V, Q>(root: &mut Link<K, V, Color>, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        // the top-down deletion below relies on the key being present
+        find(root, key)?;
+        let mut top = root.take()?;
+        if !is_red(&top.left) && !is_red(&top.right) {
+            top.meta = Color::Red;
+        }
+        let (rest, value) = rb_remove(top, key);
+        *root = rest;
+        if let Some(root) = root.as_mut() {
+            root.meta = Color::Black;
+        }
+        Some(value)
+    }
+
+    fn check<K, V>(root: &Link<K, V, Color>) -> Result<(), String> {
+        if is_red(root) {
+            return Err("the root is red".to_string());
+        }
+        rb_check(root).map(|_| ())
+    }
+}
+
+fn is_red<K, V>(link: &Link<K, V, Color>) -> bool {
+    matches!(link, Some(node) if node.meta == Color::Red)
+}
+
+fn rb_rotate_left<K, V>(node: RbNode<K, V>) -> RbNode<K, V> {
+    let color = node.meta;
+    let mut top = rotate_left(node);
+    top.meta = color;
+    if let Some(left) = top.left.as_mut() {
+        left.meta = Color::Red;
+    }
+    top
+}
+
+fn rb_rotate_right<K, V>(node: RbNode<K, V>) -> RbNode<K, V> {
+    let color = node.meta;
+    let mut top = rotate_right(node);
+    top.meta = color;
+    if let Some(right) = top.right.as_mut() {
+        right.meta = Color::Red;
+    }
+    top
+}
+
+fn flip_colors<K, V>(node: &mut TreeNode<K, V, Color>) {
+    node.meta = node.meta.flip();
+    for child in node.left.iter_mut().chain(node.right.iter_mut()) {
+        child.meta = child.meta.flip();
+    }
+}
+
+// Restore the left-leaning invariants on the way back up
+fn rb_fix_up<K, V>(mut node: RbNode<K, V>) -> RbNode<K, V> {
+    if is_red(&node.right) && !is_red(&node.left) {
+        node = rb_rotate_left(node);
+    }
+    if is_red(&node.left) && node.left.as_ref().is_some_and(|left| is_red(&left.left)) {
+        node = rb_rotate_right(node);
+    }
+    if is_red(&node.left) && is_red(&node.right) {
+        flip_colors(&mut node);
+    }
+    update_size(&mut node);
+    node
+}
+
+fn rb_insert<K: Ord, V>(link: &mut Link<K, V, Color>, key: K, value: V) -> Option<V> {
+    let mut node = match link.take() {
+        Some(node) => node,
+        None => {
+            *link = Some(Box::new(TreeNode::new(key, value, Color::Red)));
+            return None;
+        }
+    };
+    let old = match key.cmp(&node.key) {
+        Ordering::Less => rb_insert(&mut node.left, key, value),
+        Ordering::Greater => rb_insert(&mut node.right, key, value),
+        Ordering::Equal => Some(mem::replace(&mut node.value, value)),
+    };
+    *link = Some(rb_fix_up(node));
+    old
+}
+
+// Make the left child or one of its children red before descending into it
+fn move_red_left<K, V>(mut node: RbNode<K, V>) -> RbNode<K, V> {
+    flip_colors(&mut node);
+    if node.right.as_ref().is_some_and(|right| is_red(&right.left)) {
+        node.right = node.right.take().map(rb_rotate_right);
+        node = rb_rotate_left(node);
+        flip_colors(&mut node);
+    }
+    node
+}
+
+// Make the right child or one of its children red before descending into it
+fn move_red_right<K, V>(mut node: RbNode<K, V>) -> RbNode<K, V> {
+    flip_colors(&mut node);
+    if node.left.as_ref().is_some_and(|left| is_red(&left.left)) {
+        node = rb_rotate_right(node);
+        flip_colors(&mut node);
+    }
+    node
+}
+
+fn left_is_2_node<K, V>(node: &TreeNode<K, V, Color>) -> bool {
+    !is_red(&node.left) && !node.left.as_ref().is_some_and(|left| is_red(&left.left))
+}
+
+fn right_is_2_node<K, V>(node: &TreeNode<K, V, Color>) -> bool {
+    !is_red(&node.right) && !node.right.as_ref().is_some_and(|right| is_red(&right.left))
+}
+
+// Remove the minimum of a subtree, returning what is left of it and the removed node
+fn rb_remove_min<K, V>(mut node: RbNode<K, V>) -> (Link<K, V, Color>, RbNode<K, V>) {
+    let left = match node.left.take() {
+        Some(left) => left,
+        None => return (node.right.take(), node),
+    };
+    node.left = Some(left);
+    if left_is_2_node(&node) {
+        node = move_red_left(node);
+    }
+    let left = node.left.take().expect("the minimum is on the left");
+    let (rest, min) = rb_remove_min(left);
+    node.left = rest;
+    (Some(rb_fix_up(node)), min)
+}
+
+// Remove a key known to be in the subtree, returning what is left of it and the value
+fn rb_remove<K, V, Q>(mut node: RbNode<K, V>, key: &Q) -> (Link<K, V, Color>, V)
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    let value;
+    if key < node.key.borrow() {
+        if left_is_2_node(&node) {
+            node = move_red_left(node);
+        }
+        let left = node.left.take().expect("the key is on the left");
+        let (rest, removed) = rb_remove(left, key);
+        node.left = rest;
+        value = removed;
+    } else {
+        if is_red(&node.left) {
+            node = rb_rotate_right(node);
+        }
+        if key == node.key.borrow() && node.right.is_none() {
+            return (node.left.take(), node.value);
+        }
+        if right_is_2_node(&node) {
+            node = move_red_right(node);
+        }
+        let right = node
+            .right
+            .take()
+            .expect("the key or its successor is on the right");
+        if key == node.key.borrow() {
+            let (rest, mut successor) = rb_remove_min(right);
+            node.right = rest;
+            mem::swap(&mut node.key, &mut successor.key);
+            value = mem::replace(&mut node.value, successor.value);
+        } else {
+            let (rest, removed) = rb_remove(right, key);
+            node.right = rest;
+            value = removed;
+        }
+    }
+    (Some(rb_fix_up(node)), value)
+}
+
+// Returns the black height of a valid subtree
+fn rb_check<K, V>(link: &Link<K, V, Color>) -> Result<usize, String> {
+    let node = match link {
+        Some(node) => node,
+        None => return Ok(0),
+    };
+    if is_red(&node.right) {
+        return Err("red link leaning right".to_string());
+    }
+    if node.meta == Color::Red && is_red(&node.left) {
+        return Err("two red links in a row".to_string());
+    }
+    let left = rb_check(&node.left)?;
+    let right = rb_check(&node.right)?;
+    if left != right {
+        return Err(format!(
+            "black height {} on the left but {} on the right",
+            left, right
+        ));
+    }
+    Ok(left + usize::from(node.meta == Color::Black))
+}
+
+impl<K, V, B: Balance> Drop for SearchTree<K, V, B> {
+    fn drop(&mut self) {
+        // the default drop recurses once per level, which a degenerate tree cannot afford
+        let mut stack: Vec<Box<TreeNode<K, V, B::Meta>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+impl<K: Ord, V, B: Balance> FromIterator<(K, V)> for SearchTree<K, V, B> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl<'a, K: Ord, V, B: Balance> IntoIterator for &'a SearchTree<K, V, B> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, B::Meta>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord + Debug, V: Debug, B: Balance> Debug for SearchTree<K, V, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+struct Iter<'a, K, V, M> {
+    stack: Vec<&'a TreeNode<K, V, M>>,
+    remaining: usize,
+}
+
+impl<'a, K, V, M> Iter<'a, K, V, M> {
+    fn push_left_spine(&mut self, mut link: Option<&'a TreeNode<K, V, M>>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = node.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V, M> Iterator for Iter<'a, K, V, M> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(node.right.as_deref());
+        self.remaining -= 1;
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V, M> ExactSizeIterator for Iter<'_, K, V, M> {}
+
+struct PreOrder<'a, K, V, M> {
+    stack: Vec<&'a TreeNode<K, V, M>>,
+}
+
+impl<'a, K, V, M> Iterator for PreOrder<'a, K, V, M> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        // right first so that the left subtree is popped first
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+        Some((&node.key, &node.value))
+    }
+}
+
+struct PostOrder<'a, K, V, M> {
+    // each node is kept with a flag telling whether its children were already pushed
+    stack: Vec<(&'a TreeNode<K, V, M>, bool)>,
+}
+
+impl<'a, K, V, M> Iterator for PostOrder<'a, K, V, M> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, expanded) = self.stack.pop()?;
+            if expanded {
+                return Some((&node.key, &node.value));
+            }
+            self.stack.push((node, true));
+            self.stack
+                .extend(node.right.as_deref().map(|right| (right, false)));
+            self.stack
+                .extend(node.left.as_deref().map(|left| (left, false)));
+        }
+    }
+}
+
+struct LevelOrder<'a, K, V, M> {
+    queue: VecDeque<&'a TreeNode<K, V, M>>,
+}
+
+impl<'a, K, V, M> Iterator for LevelOrder<'a, K, V, M> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend(node.left.as_deref());
+        self.queue.extend(node.right.as_deref());
+        Some((&node.key, &node.value))
     }
-    fn search(&self, value: T) -> bool {
-        match value.cmp(&self.value) {
-            Ordering::Less => self.left.as_ref().is_some_and(|left| left.search(value)),
-            Ordering::Equal => true,
-            Ordering::Greater => self.right.as_ref().is_some_and(|right| right.search(value)),
+}
+
+struct Range<'a, K, V, M, Q: ?Sized, R> {
+    stack: Vec<&'a TreeNode<K, V, M>>,
+    range: R,
+    marker: PhantomData<fn(&Q)>,
+}
+
+impl<'a, K, V, M, Q, R> Iterator for Range<'a, K, V, M, Q, R>
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+    R: RangeBounds<Q>,
+{
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        if !below_upper(self.range.end_bound(), node.key.borrow()) {
+            self.stack.clear();
+            return None;
+        }
+        // everything in the right subtree is above this key, hence above the lower bound
+        let mut link = node.right.as_deref();
+        while let Some(next) = link {
+            self.stack.push(next);
+            link = next.left.as_deref();
         }
+        Some((&node.key, &node.value))
     }
 }
 
@@ -106,34 +1081,36 @@ mod tests {
 
     #[test]
     fn test_insert_and_search() {
-        let mut bst = BinarySearchTree::new();
+        let mut bst = BstMap::new();
 
-        assert_eq!(bst.search(1), false);
+        assert!(!bst.contains_key(&1));
 
-        bst.insert(5);
-        bst.insert(3);
-        bst.insert(7);
-        bst.insert(2);
-        bst.insert(4);
+        bst.insert(5, "five");
+        bst.insert(3, "three");
+        bst.insert(7, "seven");
+        bst.insert(2, "two");
+        bst.insert(4, "four");
 
-        assert_eq!(bst.search(5), true);
-        assert_eq!(bst.search(3), true);
-        assert_eq!(bst.search(7), true);
-        assert_eq!(bst.search(2), true);
-        assert_eq!(bst.search(4), true);
+        assert!(bst.contains_key(&5));
+        assert!(bst.contains_key(&3));
+        assert!(bst.contains_key(&7));
+        assert!(bst.contains_key(&2));
+        assert_eq!(bst.get(&4), Some(&"four"));
 
-        assert_eq!(bst.search(1), false);
-        assert_eq!(bst.search(6), false);
+        assert!(!bst.contains_key(&1));
+        assert_eq!(bst.get(&6), None);
+        assert_eq!(bst.len(), 5);
     }
 
     #[test]
     fn test_insert_duplicate() {
-        let mut bst = BinarySearchTree::new();
+        let mut bst = BstMap::new();
 
-        bst.insert(1);
-        bst.insert(1);
+        assert_eq!(bst.insert(1, 'a'), None);
+        assert_eq!(bst.insert(1, 'b'), Some('a'));
 
-        assert_eq!(bst.search(1), true);
+        assert_eq!(bst.get(&1), Some(&'b'));
+        assert_eq!(bst.len(), 1);
 
         match bst.root {
             Some(ref node) => {
@@ -143,4 +1120,231 @@ mod tests {
             None => panic!("Root should not be None after insertion"),
         }
     }
+
+    fn sample() -> BstMap<i32, String> {
+        //        8
+        //      /   \
+        //     3     10
+        //    / \      \
+        //   1   6      14
+        //      / \    /
+        //     4   7  13
+        [8, 3, 10, 1, 6, 14, 4, 7, 13]
+            .iter()
+            .map(|&k| (k, k.to_string()))
+            .collect()
+    }
+
+    fn keys<'a>(iter: impl Iterator<Item = (&'a i32, &'a String)>) -> Vec<i32> {
+        iter.map(|(k, _)| *k).collect()
+    }
+
+    #[test]
+    fn test_traversals() {
+        let map = sample();
+        assert_eq!(keys(map.iter()), vec![1, 3, 4, 6, 7, 8, 10, 13, 14]);
+        assert_eq!(keys(map.pre_order()), vec![8, 3, 1, 6, 4, 7, 10, 14, 13]);
+        assert_eq!(keys(map.post_order()), vec![1, 4, 7, 6, 3, 13, 14, 10, 8]);
+        assert_eq!(keys(map.level_order()), vec![8, 3, 10, 1, 6, 14, 4, 7, 13]);
+        assert_eq!(map.iter().len(), 9);
+        assert_eq!(map.keys().last(), Some(&14));
+        assert_eq!(map.values().next().map(String::as_str), Some("1"));
+    }
+
+    #[test]
+    fn test_min_max_floor_ceiling() {
+        let map = sample();
+        assert_eq!(map.min().map(|(k, _)| *k), Some(1));
+        assert_eq!(map.max().map(|(k, _)| *k), Some(14));
+        assert_eq!(map.floor(&5).map(|(k, _)| *k), Some(4));
+        assert_eq!(map.floor(&6).map(|(k, _)| *k), Some(6));
+        assert_eq!(map.floor(&0), None);
+        assert_eq!(map.ceiling(&11).map(|(k, _)| *k), Some(13));
+        assert_eq!(map.ceiling(&15), None);
+        assert_eq!(BstMap::<i32, ()>::new().min(), None);
+    }
+
+    #[test]
+    fn test_range() {
+        let map = sample();
+        assert_eq!(keys(map.range(4..10)), vec![4, 6, 7, 8]);
+        assert_eq!(keys(map.range(5..=13)), vec![6, 7, 8, 10, 13]);
+        assert_eq!(keys(map.range(..3)), vec![1]);
+        assert_eq!(keys(map.range(11..)), vec![13, 14]);
+        assert_eq!(keys(map.range(20..30)), Vec::<i32>::new());
+
+        let words: BstMap<String, usize> = ["pear", "apple", "fig", "plum"]
+            .iter()
+            .map(|w| (w.to_string(), w.len()))
+            .collect();
+        // borrowed `str` bounds on `String` keys
+        let bounds = (Bound::Included("b"), Bound::Excluded("pf"));
+        let found: Vec<&String> = words.range::<str, _>(bounds).map(|(k, _)| k).collect();
+        assert_eq!(found, vec!["fig", "pear"]);
+    }
+
+    #[test]
+    fn test_get_mut_and_remove() {
+        let mut map = sample();
+        map.get_mut(&6).unwrap().push('!');
+        assert_eq!(map.get(&6).map(String::as_str), Some("6!"));
+
+        // leaf, node with one child, node with two children, root
+        assert_eq!(map.remove(&4).as_deref(), Some("4"));
+        assert_eq!(map.remove(&14).as_deref(), Some("14"));
+        assert_eq!(map.remove(&3).as_deref(), Some("3"));
+        assert_eq!(map.remove(&8).as_deref(), Some("8"));
+        assert_eq!(map.remove(&8), None);
+        assert_eq!(keys(map.iter()), vec![1, 6, 7, 10, 13]);
+        assert_eq!(map.len(), 5);
+        assert!(!map.is_empty());
+    }
+
+    #[test]
+    fn test_sorted_input_does_not_overflow() {
+        let mut map: BstMap<u32, u32> = (0..20_000).map(|k| (k, k)).collect();
+        assert_eq!(map.get(&19_999), Some(&19_999));
+        assert_eq!(map.remove(&10_000), Some(10_000));
+        assert_eq!(map.iter().count(), 19_999);
+    }
+
+    // pseudo-random numbers without external crates
+    fn lcg(state: &mut u64) -> u64 {
+        *state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        *state >> 33
+    }
+
+    // random inserts and removes checked against `BTreeMap`, validating after every operation
+    fn random_operations<B: Balance>(seed: u64) {
+        let mut state = seed;
+        let mut map = SearchTree::<u64, u64, B>::new();
+        let mut model = std::collections::BTreeMap::new();
+        for step in 0..3000 {
+            let key = lcg(&mut state) % 300;
+            // one operation in three is a removal
+            if lcg(&mut state) % 3 == 2 {
+                assert_eq!(map.remove(&key), model.remove(&key), "remove {}", key);
+            } else {
+                assert_eq!(
+                    map.insert(key, step),
+                    model.insert(key, step),
+                    "insert {}",
+                    key
+                );
+            }
+            if let Err(message) = map.check_invariants() {
+                panic!(
+                    "invariant broken after step {} (key {}): {}",
+                    step, key, message
+                );
+            }
+            assert_eq!(map.len(), model.len());
+        }
+        assert!(map.iter().eq(model.iter()));
+        // drain completely, validating on the way
+        let keys: Vec<u64> = model.keys().copied().collect();
+        for key in keys {
+            assert_eq!(map.remove(&key), model.remove(&key));
+            assert_eq!(map.check_invariants(), Ok(()));
+        }
+        assert!(map.is_empty() && map.root.is_none());
+    }
+
+    #[test]
+    fn test_random_operations_keep_invariants() {
+        for seed in 1..4 {
+            random_operations::<Unbalanced>(seed);
+            random_operations::<Avl>(seed);
+            random_operations::<RedBlack>(seed);
+        }
+    }
+
+    #[test]
+    fn test_sorted_input_stays_balanced() {
+        let n: u32 = 100_000;
+        let avl: AvlMap<u32, ()> = (0..n).map(|k| (k, ())).collect();
+        let rb: RbMap<u32, ()> = (0..n).rev().map(|k| (k, ())).collect();
+        let log2 = (n as f64 + 1.0).log2();
+        assert!(
+            (avl.height() as f64) <= 1.45 * log2,
+            "AVL height {}",
+            avl.height()
+        );
+        assert!(
+            (rb.height() as f64) <= 2.0 * log2,
+            "red-black height {}",
+            rb.height()
+        );
+        assert_eq!(avl.check_invariants(), Ok(()));
+        assert_eq!(rb.check_invariants(), Ok(()));
+
+        let bst: BstMap<u32, ()> = (0..1000).map(|k| (k, ())).collect();
+        assert_eq!(bst.height(), 1000);
+    }
+
+    #[test]
+    fn test_balanced_maps_share_the_api() {
+        fn check<B: Balance>() {
+            let mut map: SearchTree<i32, String, B> = [8, 3, 10, 1, 6, 14, 4, 7, 13]
+                .iter()
+                .map(|&k| (k, k.to_string()))
+                .collect();
+            assert_eq!(map.floor(&5).map(|(k, _)| *k), Some(4));
+            assert_eq!(map.ceiling(&11).map(|(k, _)| *k), Some(13));
+            assert_eq!((map.min().unwrap().0, map.max().unwrap().0), (&1, &14));
+            assert_eq!(keys(map.range(4..10)), vec![4, 6, 7, 8]);
+            map.get_mut(&6).unwrap().push('!');
+            assert_eq!(map.remove(&6).as_deref(), Some("6!"));
+            assert_eq!(map.pre_order().count(), 8);
+            assert_eq!(map.post_order().count(), 8);
+            assert_eq!(map.level_order().next(), map.pre_order().next());
+            assert!(format!("{:?}", map).starts_with(r#"{1: "1", 3: "3", 4: "4", 7: "7""#));
+        }
+        check::<Unbalanced>();
+        check::<Avl>();
+        check::<RedBlack>();
+    }
+    #[test]
+    fn test_rank_select_count_range() {
+        fn check<B: Balance>(seed: u64) {
+            let mut state = seed;
+            let mut map: SearchTree<u64, (), B> = SearchTree::new();
+            let mut model = std::collections::BTreeMap::new();
+            for round in 0..2000 {
+                let key = lcg(&mut state) % 500;
+                if round % 3 == 2 {
+                    map.remove(&key);
+                    model.remove(&key);
+                } else {
+                    map.insert(key, ());
+                    model.insert(key, ());
+                }
+            }
+            assert_eq!(map.check_invariants(), Ok(()));
+            let sorted: Vec<u64> = model.keys().cloned().collect();
+            for (k, key) in sorted.iter().enumerate() {
+                assert_eq!(map.rank(key), k);
+                assert_eq!(map.select(k).map(|(key, _)| *key), Some(*key));
+            }
+            assert_eq!(map.select(sorted.len()), None);
+            for _ in 0..200 {
+                let a = lcg(&mut state) % 520;
+                let b = lcg(&mut state) % 520;
+                assert_eq!(map.rank(&a), model.range(..a).count());
+                // `BTreeMap::range` panics on inverted bounds, so count by filtering instead
+                let count =
+                    |range: &dyn Fn(&u64) -> bool| model.keys().filter(|k| range(k)).count();
+                assert_eq!(map.count_range(a..b), count(&|k| (a..b).contains(k)));
+                assert_eq!(map.count_range(a..=b), count(&|k| (a..=b).contains(k)));
+                assert_eq!(map.count_range(a..), model.range(a..).count());
+                assert_eq!(map.count_range(..=b), model.range(..=b).count());
+            }
+            assert_eq!(map.count_range::<u64, _>(..), map.len());
+        }
+        check::<Unbalanced>(7);
+        check::<Avl>(7);
+        check::<RedBlack>(7);
+    }
 }
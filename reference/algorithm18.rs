@@ -0,0 +1,16 @@
+/*
+    Reference solution for easy/algorithm18.rs (Merge Intervals)
+    Used by the runner's differential tests, not graded.
+*/
+
+pub fn merge_intervals(mut intervals: Vec<Vec<i32>>) -> Vec<Vec<i32>> {
+    intervals.sort_by_key(|interval| interval[0]);
+    let mut merged: Vec<Vec<i32>> = Vec::new();
+    for interval in intervals {
+        match merged.last_mut() {
+            Some(last) if interval[0] <= last[1] => last[1] = last[1].max(interval[1]),
+            _ => merged.push(interval),
+        }
+    }
+    merged
+}
@@ -0,0 +1,27 @@
+/*
+    Reference solution for normal/solution2 (base conversion)
+    Used by the runner's differential tests, not graded.
+*/
+
+pub fn convert_base(num_str: &str, to_base: u32) -> String {
+    let (digits, from_base) = num_str
+        .trim_end_matches(')')
+        .split_once('(')
+        .expect("input must look like `digits(base)`");
+    let from_base: u32 = from_base.parse().expect("invalid source base");
+    let value = u64::from_str_radix(digits, from_base).expect("invalid digits");
+    to_radix(value, to_base)
+}
+
+// 按给定进制输出数字，大于 9 的位使用小写字母
+pub fn to_radix(mut value: u64, base: u32) -> String {
+    if value == 0 {
+        return "0".to_string();
+    }
+    let mut digits = Vec::new();
+    while value > 0 {
+        digits.push(std::char::from_digit((value % base as u64) as u32, base).unwrap());
+        value /= base as u64;
+    }
+    digits.iter().rev().collect()
+}
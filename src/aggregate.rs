@@ -0,0 +1,383 @@
+use crate::schema;
+use crate::ExerciseConfig;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+const DIFFICULTIES: [&str; 3] = ["easy", "normal", "hard"];
+const HARDEST_SHOWN: usize = 5;
+const HISTOGRAM_BUCKETS: usize = 5;
+
+// 汇总时只读取需要的字段：旧版本先经 schema::migrate 迁移，缺失字段取默认值，未知字段忽略
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct ReportView {
+    exercises: Vec<ExerciseView>,
+    statistics: Option<StatisticsView>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct ExerciseView {
+    name: String,
+    result: bool,
+    score: i32,
+    difficulty: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct StatisticsView {
+    total_score: Option<i32>,
+}
+
+pub struct Student {
+    pub name: String,
+    pub total_score: i32,
+    pub passed: usize,
+    // 习题名 -> (是否通过, 得分)
+    pub results: BTreeMap<String, (bool, i32)>,
+}
+
+pub struct ExerciseStats {
+    pub name: String,
+    pub difficulty: String,
+    pub attempts: usize,
+    pub passes: usize,
+}
+
+impl ExerciseStats {
+    pub fn pass_rate(&self) -> f64 {
+        if self.attempts == 0 {
+            0.0
+        } else {
+            self.passes as f64 / self.attempts as f64
+        }
+    }
+}
+
+pub struct Aggregate {
+    pub students: Vec<Student>,
+    pub exercises: Vec<ExerciseStats>,
+    // 难度 -> 每位学生在该难度下的得分
+    pub difficulty_scores: BTreeMap<String, Vec<i32>>,
+    // 难度 -> 该难度的满分（来自配置文件）
+    pub difficulty_max: BTreeMap<String, i32>,
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+// 读取目录下的全部报告（递归查找 *.json）。学生名取文件名，文件名为 report.json 时取所在目录名。
+pub fn aggregate(dir: &Path, config: Option<&ExerciseConfig>) -> io::Result<Aggregate> {
+    let mut paths = Vec::new();
+    collect_reports(dir, &mut paths)?;
+    paths.sort();
+
+    // 习题名 -> 难度，优先使用配置文件，其次使用报告中自带的难度
+    let mut difficulty_of: BTreeMap<String, String> = BTreeMap::new();
+    let mut difficulty_max: BTreeMap<String, i32> = BTreeMap::new();
+    let mut order: Vec<String> = Vec::new();
+    if let Some(config) = config {
+        for (difficulty, exercises) in [
+            ("easy", &config.easy),
+            ("normal", &config.normal),
+            ("hard", &config.hard),
+        ] {
+            for exercise in exercises {
+                difficulty_of.insert(exercise.name.clone(), difficulty.to_string());
+                *difficulty_max.entry(difficulty.to_string()).or_default() += exercise.score;
+                order.push(exercise.name.clone());
+            }
+        }
+    }
+
+    let mut students = Vec::new();
+    let mut skipped = Vec::new();
+    for path in paths {
+        let report: ReportView = match File::open(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|file| serde_json::from_reader(file).map_err(|e| e.to_string()))
+            .and_then(|value| schema::migrate(value).map_err(|e| e.to_string()))
+            .and_then(|value| serde_json::from_value(value).map_err(|e| e.to_string()))
+        {
+            Ok(report) => report,
+            Err(e) => {
+                skipped.push((path, e));
+                continue;
+            }
+        };
+        let mut results = BTreeMap::new();
+        for exercise in &report.exercises {
+            if let Some(difficulty) = &exercise.difficulty {
+                difficulty_of
+                    .entry(exercise.name.clone())
+                    .or_insert_with(|| difficulty.clone());
+            }
+            if !order.contains(&exercise.name) {
+                order.push(exercise.name.clone());
+            }
+            results.insert(exercise.name.clone(), (exercise.result, exercise.score));
+        }
+        let total_score = report
+            .statistics
+            .and_then(|s| s.total_score)
+            .unwrap_or_else(|| results.values().map(|&(_, score)| score).sum());
+        students.push(Student {
+            name: student_name(&path),
+            total_score,
+            passed: results.values().filter(|&&(result, _)| result).count(),
+            results,
+        });
+    }
+    students.sort_by(|a, b| {
+        b.total_score
+            .cmp(&a.total_score)
+            .then(b.passed.cmp(&a.passed))
+            .then(a.name.cmp(&b.name))
+    });
+
+    let exercises = order
+        .iter()
+        .map(|name| {
+            let outcomes: Vec<bool> = students
+                .iter()
+                .filter_map(|s| s.results.get(name).map(|&(result, _)| result))
+                .collect();
+            ExerciseStats {
+                name: name.clone(),
+                difficulty: difficulty_of
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| "unknown".to_string()),
+                attempts: outcomes.len(),
+                passes: outcomes.iter().filter(|&&passed| passed).count(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut difficulty_scores: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+    for student in &students {
+        let mut subtotals: BTreeMap<&str, i32> = BTreeMap::new();
+        for exercise in &exercises {
+            subtotals.entry(&exercise.difficulty).or_default();
+            if let Some(&(_, score)) = student.results.get(&exercise.name) {
+                *subtotals.entry(&exercise.difficulty).or_default() += score;
+            }
+        }
+        for (difficulty, score) in subtotals {
+            difficulty_scores
+                .entry(difficulty.to_string())
+                .or_default()
+                .push(score);
+        }
+    }
+
+    Ok(Aggregate {
+        students,
+        exercises,
+        difficulty_scores,
+        difficulty_max,
+        skipped,
+    })
+}
+
+impl Aggregate {
+    pub fn print(&self) {
+        for (path, error) in &self.skipped {
+            eprintln!("Skipped {}: {}", path.display(), error);
+        }
+
+        println!("Ranking ({} students):", self.students.len());
+        println!(
+            "{:>4}  {:<24} {:>6} {:>7}",
+            "#", "Student", "Score", "Passed"
+        );
+        for (rank, student) in self.students.iter().enumerate() {
+            println!(
+                "{:>4}  {:<24} {:>6} {:>7}",
+                rank + 1,
+                student.name,
+                student.total_score,
+                student.passed
+            );
+        }
+
+        println!("\nPass rates:");
+        for exercise in &self.exercises {
+            println!(
+                "  {:<16} {:<8} {:>3}/{:<3} {:>5.1}%",
+                exercise.name,
+                exercise.difficulty,
+                exercise.passes,
+                exercise.attempts,
+                exercise.pass_rate() * 100.0
+            );
+        }
+
+        println!("\nHardest exercises:");
+        for exercise in self.hardest().into_iter().take(HARDEST_SHOWN) {
+            println!(
+                "  {:<16} {:<8} {:>5.1}%",
+                exercise.name,
+                exercise.difficulty,
+                exercise.pass_rate() * 100.0
+            );
+        }
+
+        println!("\nScore distribution by difficulty:");
+        for (difficulty, scores) in self.difficulties() {
+            let mut sorted = scores.clone();
+            sorted.sort_unstable();
+            let (Some(&min), Some(&max)) = (sorted.first(), sorted.last()) else {
+                continue;
+            };
+            let mean = sorted.iter().sum::<i32>() as f64 / sorted.len() as f64;
+            println!(
+                "  {:<8} min {:>4}  median {:>4}  mean {:>6.1}  max {:>4}",
+                difficulty,
+                min,
+                sorted[sorted.len() / 2],
+                mean,
+                max
+            );
+            let full = self
+                .difficulty_max
+                .get(difficulty)
+                .copied()
+                .unwrap_or(max)
+                .max(1);
+            for (range, count) in histogram(&sorted, full) {
+                println!("    {:>11} | {}", range, "#".repeat(count));
+            }
+        }
+    }
+
+    // 通过率从低到高，相同通过率时难度高的在前
+    pub fn hardest(&self) -> Vec<&ExerciseStats> {
+        let mut exercises: Vec<&ExerciseStats> =
+            self.exercises.iter().filter(|e| e.attempts > 0).collect();
+        exercises.sort_by(|a, b| {
+            a.pass_rate()
+                .total_cmp(&b.pass_rate())
+                .then(difficulty_rank(&b.difficulty).cmp(&difficulty_rank(&a.difficulty)))
+        });
+        exercises
+    }
+
+    fn difficulties(&self) -> Vec<(&String, &Vec<i32>)> {
+        let mut difficulties: Vec<_> = self.difficulty_scores.iter().collect();
+        difficulties.sort_by_key(|(difficulty, _)| difficulty_rank(difficulty));
+        difficulties
+    }
+
+    // 每位学生一行：总分、通过数、各难度小计与各题得分
+    pub fn write_csv(&self, path: &Path) -> io::Result<()> {
+        let mut out = io::BufWriter::new(File::create(path)?);
+        let difficulties: Vec<&String> = self.difficulties().into_iter().map(|(d, _)| d).collect();
+        let mut header = vec![
+            "rank".to_string(),
+            "student".to_string(),
+            "total_score".to_string(),
+            "passed".to_string(),
+        ];
+        header.extend(difficulties.iter().map(|d| format!("{}_score", d)));
+        header.extend(self.exercises.iter().map(|e| e.name.clone()));
+        writeln!(out, "{}", csv_row(&header))?;
+
+        for (rank, student) in self.students.iter().enumerate() {
+            let mut row = vec![
+                (rank + 1).to_string(),
+                student.name.clone(),
+                student.total_score.to_string(),
+                student.passed.to_string(),
+            ];
+            for difficulty in &difficulties {
+                let subtotal: i32 = self
+                    .exercises
+                    .iter()
+                    .filter(|e| &&e.difficulty == difficulty)
+                    .filter_map(|e| student.results.get(&e.name))
+                    .map(|&(_, score)| score)
+                    .sum();
+                row.push(subtotal.to_string());
+            }
+            for exercise in &self.exercises {
+                row.push(
+                    student
+                        .results
+                        .get(&exercise.name)
+                        .map(|&(_, score)| score.to_string())
+                        .unwrap_or_default(),
+                );
+            }
+            writeln!(out, "{}", csv_row(&row))?;
+        }
+        out.flush()
+    }
+}
+
+fn difficulty_rank(difficulty: &str) -> usize {
+    DIFFICULTIES
+        .iter()
+        .position(|&d| d == difficulty)
+        .unwrap_or(DIFFICULTIES.len())
+}
+
+// 将 [0, full] 等分为若干区间并统计人数
+fn histogram(sorted: &[i32], full: i32) -> Vec<(String, usize)> {
+    let width = (full as usize).div_ceil(HISTOGRAM_BUCKETS).max(1) as i32;
+    (0..HISTOGRAM_BUCKETS as i32)
+        .map(|bucket| {
+            let lo = bucket * width;
+            let hi = if bucket == HISTOGRAM_BUCKETS as i32 - 1 {
+                i32::MAX
+            } else {
+                lo + width - 1
+            };
+            let count = sorted.iter().filter(|&&s| s >= lo && s <= hi).count();
+            let label = if hi == i32::MAX {
+                format!("{}+", lo)
+            } else {
+                format!("{}-{}", lo, hi)
+            };
+            (label, count)
+        })
+        .collect()
+}
+
+fn csv_row(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn student_name(path: &Path) -> String {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    if stem == "report" {
+        if let Some(parent) = path.parent().and_then(|p| p.file_name()) {
+            return parent.to_string_lossy().to_string();
+        }
+    }
+    stem.to_string()
+}
+
+fn collect_reports(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_reports(&path, paths)?;
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,431 @@
+use crate::harness::Harness;
+use crate::source::{is_test_item, SourceFile};
+use quote::ToTokens;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use syn::visit_mut::{self, VisitMut};
+use syn::{parse_quote, Block, Expr, Item, Lit, Pat, Stmt};
+
+// 每个用例的预热次数、最多/最少计时次数与时间预算（毫秒）
+const WARMUP_RUNS: usize = 3;
+const MAX_RUNS: usize = 50;
+const MIN_RUNS: usize = 5;
+const CASE_BUDGET_MS: u64 = 2000;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BenchCase {
+    pub exercise: String,
+    pub test: String,
+    // 同一测试函数中的第几次计时（循环中的每个测试用例各算一次）
+    pub case: usize,
+    pub runs: usize,
+    pub median_nanos: u64,
+    pub p95_nanos: u64,
+    pub stddev_nanos: u64,
+    // 测试代码中的时间上限
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold_nanos: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    // p95 在上限减去置信余量之内
+    Pass,
+    // 中位数未超过上限，但 p95 落在余量内或超过上限，评测时可能因抖动失败
+    Marginal,
+    // 中位数超过上限
+    Slow,
+    // 测试代码中没有可识别的上限
+    Unbounded,
+}
+
+impl BenchCase {
+    pub fn key(&self) -> (&str, &str, usize) {
+        (&self.exercise, &self.test, self.case)
+    }
+
+    pub fn verdict(&self, margin: f64) -> Verdict {
+        let Some(threshold) = self.threshold_nanos else {
+            return Verdict::Unbounded;
+        };
+        if self.median_nanos > threshold {
+            Verdict::Slow
+        } else if self.p95_nanos as f64 > threshold as f64 * (1.0 - margin) {
+            Verdict::Marginal
+        } else {
+            Verdict::Pass
+        }
+    }
+
+    // 与基线相比中位数变慢超过余量，且超过基线的 p95，视为性能回退
+    pub fn regressed_from(&self, baseline: &BenchCase, margin: f64) -> bool {
+        self.median_nanos as f64 > baseline.median_nanos as f64 * (1.0 + margin)
+            && self.median_nanos > baseline.p95_nanos
+    }
+}
+
+pub struct BenchRun {
+    pub cases: Vec<BenchCase>,
+    // 测试函数名 -> panic 信息（结果错误或断言失败）
+    pub failures: Vec<(String, String)>,
+}
+
+// 计时与统计的辅助代码，替换测试中 `Instant::now()` 与 `.elapsed()` 之间的代码
+const MEASURE_SOURCE: &str = r#"
+fn __bench_measure<T>(test: &str, site: usize, mut run: impl FnMut() -> T) -> (T, std::time::Duration) {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+    static CASE: AtomicUsize = AtomicUsize::new(0);
+    let case = CASE.fetch_add(1, Ordering::SeqCst);
+    for _ in 0..__BENCH_WARMUP {
+        std::hint::black_box(run());
+    }
+    let mut samples: Vec<u64> = Vec::new();
+    let budget = Instant::now();
+    let mut last;
+    loop {
+        let start = Instant::now();
+        last = std::hint::black_box(run());
+        samples.push(start.elapsed().as_nanos() as u64);
+        if samples.len() >= __BENCH_MAX || (samples.len() >= __BENCH_MIN && budget.elapsed() >= Duration::from_millis(__BENCH_BUDGET_MS)) {
+            break;
+        }
+    }
+    samples.sort_unstable();
+    let median = samples[samples.len() / 2];
+    let p95 = samples[((samples.len() as f64 * 0.95).ceil() as usize).clamp(1, samples.len()) - 1];
+    let mean = samples.iter().map(|&s| s as f64).sum::<f64>() / samples.len() as f64;
+    let variance = samples.iter().map(|&s| (s as f64 - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+    println!(
+        "BENCH {} {} {} {} {} {} {}",
+        test, site, case, samples.len(), median, p95, variance.sqrt() as u64
+    );
+    (last, Duration::from_nanos(median))
+}
+"#;
+
+// 找出测试代码中的计时区间，重复运行并统计每个用例的耗时
+pub fn run(
+    exercise_name: &str,
+    exercise_path: &Path,
+    exercise_type: &str,
+    mut harness: Harness,
+    workdir: &Path,
+) -> io::Result<BenchRun> {
+    let mut items = protected_items(exercise_path, exercise_type)?;
+    let mut tests = Vec::new();
+    let mut thresholds = Vec::new();
+    for item in &mut items {
+        strip_test_attributes(item, &mut tests, &mut thresholds)?;
+    }
+    if thresholds.is_empty() {
+        return Ok(BenchRun {
+            cases: Vec::new(),
+            failures: Vec::new(),
+        });
+    }
+
+    let mut prelude = harness.solution_imports();
+    prelude.push_str(
+        &MEASURE_SOURCE
+            .replace("__BENCH_WARMUP", &WARMUP_RUNS.to_string())
+            .replace("__BENCH_MAX", &MAX_RUNS.to_string())
+            .replace("__BENCH_MIN", &MIN_RUNS.to_string())
+            .replace("__BENCH_BUDGET_MS", &CASE_BUDGET_MS.to_string()),
+    );
+    for item in &items {
+        prelude.push_str("#[allow(dead_code, unused_imports, unused_variables, unused_mut)]\n");
+        prelude.push_str(&item.to_token_stream().to_string());
+        prelude.push('\n');
+    }
+    harness.prelude = prelude;
+    harness.main_body = String::from("    std::panic::set_hook(Box::new(|_| {}));\n");
+    for (path, name) in &tests {
+        harness.main_body.push_str(&format!(
+            r#"    if let Err(payload) = std::panic::catch_unwind(|| {path}()) {{
+        let message = payload.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        println!("PANIC {name} {{}}", message.replace('\n', " "));
+    }}
+"#,
+        ));
+    }
+
+    let output = harness.run(workdir)?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "benchmark exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let mut cases = Vec::new();
+    let mut failures = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(message) = line.strip_prefix("PANIC ") {
+            let (test, message) = message.split_once(' ').unwrap_or((message, ""));
+            failures.push((test.to_string(), message.to_string()));
+            continue;
+        }
+        let Some(fields) = line.strip_prefix("BENCH ") else {
+            continue;
+        };
+        let fields: Vec<&str> = fields.split(' ').collect();
+        let [test, site, case, runs, median, p95, stddev] = fields[..] else {
+            continue;
+        };
+        let number = |field: &str| field.parse::<u64>().unwrap_or_default();
+        cases.push(BenchCase {
+            exercise: exercise_name.to_string(),
+            test: test.to_string(),
+            case: number(case) as usize,
+            runs: number(runs) as usize,
+            median_nanos: number(median),
+            p95_nanos: number(p95),
+            stddev_nanos: number(stddev),
+            threshold_nanos: thresholds.get(number(site) as usize).copied().flatten(),
+        });
+    }
+    Ok(BenchRun { cases, failures })
+}
+
+// 受保护的测试代码：单文件习题为其中的 #[cfg(test)] 条目，Cargo 项目为 src/tests.rs 中除模块声明外的条目
+fn protected_items(exercise_path: &Path, exercise_type: &str) -> io::Result<Vec<Item>> {
+    match exercise_type {
+        "single_file" => Ok(SourceFile::load(exercise_path)?
+            .ast
+            .items
+            .into_iter()
+            .filter(is_test_item)
+            .collect()),
+        "cargo_project" => Ok(
+            SourceFile::load(&exercise_path.join("src").join("tests.rs"))?
+                .ast
+                .items
+                .into_iter()
+                .filter(|item| !matches!(item, Item::Mod(module) if module.content.is_none()))
+                .collect(),
+        ),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unknown exercise type: {}", exercise_type),
+        )),
+    }
+}
+
+// 去掉 #[cfg(test)] 与 #[test]，记录测试函数的路径，并改写其中的计时区间
+fn strip_test_attributes(
+    item: &mut Item,
+    tests: &mut Vec<(String, String)>,
+    thresholds: &mut Vec<Option<u64>>,
+) -> io::Result<()> {
+    fn visit(
+        item: &mut Item,
+        prefix: &str,
+        tests: &mut Vec<(String, String)>,
+        thresholds: &mut Vec<Option<u64>>,
+    ) -> io::Result<()> {
+        match item {
+            Item::Mod(module) => {
+                module.attrs.retain(|attr| !is_cfg_test(attr));
+                module.vis = parse_quote!(pub);
+                let prefix = format!("{}{}::", prefix, module.ident);
+                if let Some((_, items)) = &mut module.content {
+                    for item in items {
+                        visit(item, &prefix, tests, thresholds)?;
+                    }
+                }
+            }
+            Item::Fn(function) => {
+                let is_test = function
+                    .attrs
+                    .iter()
+                    .any(|attr| attr.path().is_ident("test"));
+                function
+                    .attrs
+                    .retain(|attr| !attr.path().is_ident("test") && !is_cfg_test(attr));
+                if !is_test {
+                    return Ok(());
+                }
+                let name = function.sig.ident.to_string();
+                let mut rewriter = TimedRegions {
+                    test: name.clone(),
+                    thresholds,
+                    error: None,
+                };
+                rewriter.visit_block_mut(&mut function.block);
+                if let Some(error) = rewriter.error {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, error));
+                }
+                // 测试函数与所在模块需对 main 可见
+                function.vis = parse_quote!(pub);
+                tests.push((format!("{}{}", prefix, name), name));
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    visit(item, "", tests, thresholds)
+}
+
+fn is_cfg_test(attr: &syn::Attribute) -> bool {
+    attr.path().is_ident("cfg") && attr.meta.to_token_stream().to_string().contains("test")
+}
+
+// 将 `let start = Instant::now(); let result = f(..); let duration = start.elapsed();`
+// 改写为重复计时的 `let (result, duration) = __bench_measure(.., || f(..));`
+struct TimedRegions<'a> {
+    test: String,
+    thresholds: &'a mut Vec<Option<u64>>,
+    error: Option<String>,
+}
+
+impl VisitMut for TimedRegions<'_> {
+    fn visit_block_mut(&mut self, block: &mut Block) {
+        let mut index = 0;
+        while index < block.stmts.len() {
+            let Some(start) = let_binding(&block.stmts[index])
+                .filter(|(_, init)| is_instant_now(init))
+                .map(|(pat, _)| pat.to_token_stream().to_string())
+            else {
+                index += 1;
+                continue;
+            };
+            let Some(end) = (index + 1..block.stmts.len()).find(|&i| {
+                let_binding(&block.stmts[i]).is_some_and(|(_, init)| is_elapsed_of(init, &start))
+            }) else {
+                index += 1;
+                continue;
+            };
+            let region = &block.stmts[index + 1..end];
+            let [Stmt::Local(timed)] = region else {
+                self.error = Some(format!(
+                    "{}: the timed region must be a single `let` statement",
+                    self.test
+                ));
+                return;
+            };
+            let (Some(init), Some((duration, _))) = (&timed.init, let_binding(&block.stmts[end]))
+            else {
+                self.error = Some(format!("{}: unsupported timed region", self.test));
+                return;
+            };
+
+            let result = &timed.pat;
+            let call = &init.expr;
+            let test = &self.test;
+            let site = self.thresholds.len();
+            let threshold = threshold_of(
+                &block.stmts[end + 1..],
+                &duration.to_token_stream().to_string(),
+            );
+            self.thresholds.push(threshold);
+            let replacement: Stmt = parse_quote! {
+                let (#result, #duration) = crate::__bench_measure(#test, #site, || #call);
+            };
+            block.stmts.splice(index..=end, [replacement]);
+            index += 1;
+        }
+        visit_mut::visit_block_mut(self, block);
+    }
+}
+
+fn let_binding(stmt: &Stmt) -> Option<(&Pat, &Expr)> {
+    match stmt {
+        Stmt::Local(local) => Some((&local.pat, &local.init.as_ref()?.expr)),
+        _ => None,
+    }
+}
+
+fn is_instant_now(expr: &Expr) -> bool {
+    matches!(expr, Expr::Call(call) if call.func.to_token_stream().to_string().replace(' ', "").ends_with("Instant::now"))
+}
+
+fn is_elapsed_of(expr: &Expr, start: &str) -> bool {
+    matches!(expr, Expr::MethodCall(call)
+        if call.method == "elapsed" && call.receiver.to_token_stream().to_string() == start)
+}
+
+// 在计时区间之后的代码中查找 `duration <= Duration::...` 形式的比较，返回上限（纳秒）
+fn threshold_of(stmts: &[Stmt], duration: &str) -> Option<u64> {
+    struct Finder<'a> {
+        duration: &'a str,
+        threshold: Option<u64>,
+    }
+    impl<'ast> syn::visit::Visit<'ast> for Finder<'_> {
+        fn visit_expr_binary(&mut self, expr: &'ast syn::ExprBinary) {
+            let inclusive = match expr.op {
+                syn::BinOp::Le(_) | syn::BinOp::Lt(_) => true,
+                syn::BinOp::Ge(_) | syn::BinOp::Gt(_) => false,
+                _ => {
+                    syn::visit::visit_expr_binary(self, expr);
+                    return;
+                }
+            };
+            let (variable, limit) = if inclusive {
+                (&expr.left, &expr.right)
+            } else {
+                (&expr.right, &expr.left)
+            };
+            if self.threshold.is_none() && variable.to_token_stream().to_string() == self.duration {
+                self.threshold = duration_nanos(limit);
+            }
+            syn::visit::visit_expr_binary(self, expr);
+        }
+    }
+    let mut finder = Finder {
+        duration,
+        threshold: None,
+    };
+    for stmt in stmts {
+        syn::visit::Visit::visit_stmt(&mut finder, stmt);
+    }
+    finder.threshold
+}
+
+// 解析 Duration::from_millis(200)、Duration::new(3, 0) 等常量表达式
+fn duration_nanos(expr: &Expr) -> Option<u64> {
+    let Expr::Call(call) = expr else {
+        return None;
+    };
+    let Expr::Path(func) = call.func.as_ref() else {
+        return None;
+    };
+    let constructor = func.path.segments.last()?.ident.to_string();
+    let args: Vec<f64> = call
+        .args
+        .iter()
+        .map(|arg| match arg {
+            Expr::Lit(lit) => match &lit.lit {
+                Lit::Int(int) => int.base10_parse::<u64>().ok().map(|v| v as f64),
+                Lit::Float(float) => float.base10_parse::<f64>().ok(),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    let duration = match (constructor.as_str(), args.as_slice()) {
+        ("from_secs" | "from_secs_f64" | "from_secs_f32", [secs]) => Duration::from_secs_f64(*secs),
+        ("from_millis", [millis]) => Duration::from_millis(*millis as u64),
+        ("from_micros", [micros]) => Duration::from_micros(*micros as u64),
+        ("from_nanos", [nanos]) => Duration::from_nanos(*nanos as u64),
+        ("new", [secs, nanos]) => Duration::new(*secs as u64, *nanos as u32),
+        _ => return None,
+    };
+    Some(duration.as_nanos() as u64)
+}
+
+pub fn load_baseline(path: &Path) -> io::Result<Vec<BenchCase>> {
+    Ok(serde_json::from_reader(File::open(path)?)?)
+}
+
+pub fn save_baseline(path: &Path, cases: &[BenchCase]) -> io::Result<()> {
+    let mut json = serde_json::to_string_pretty(cases)?;
+    json.push('\n');
+    fs::write(path, json)
+}
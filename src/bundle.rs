@@ -0,0 +1,305 @@
+use crate::harness::temp_dir;
+use crate::process;
+use crate::schema;
+use crate::{ExerciseResult, Report};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MANIFEST_NAME: &str = "manifest.json";
+const CONFIG_NAME: &str = "exercise_config.json";
+const REPORT_NAME: &str = "report.json";
+// 差分测试的参考实现目录，始终使用评测方自己的副本
+const REFERENCE_DIR: &str = "reference";
+// 重新评测的时间上限，超时（例如题解死循环）时终止评测进程
+const REGRADE_TIMEOUT: Duration = Duration::from_secs(3);
+
+// exercises 目录下打包的源码文件类型（编译产物、PDF 题面等不打包）
+const SOURCE_EXTENSIONS: &[&str] = &["rs", "toml", "lock", "json"];
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileEntry {
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Manifest {
+    pub toolchain: String,
+    pub created_at: u64,
+    pub files: Vec<FileEntry>,
+}
+
+// 校验与重新评测的结果
+pub struct Verification {
+    pub manifest: Manifest,
+    pub problems: Vec<String>,
+    // 仅由复杂度探测（计时）导致的差异，不计为问题
+    pub timing_differences: Vec<String>,
+    pub claimed: Report,
+    pub regraded: Report,
+}
+
+impl Verification {
+    pub fn is_consistent(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+// 将 exercises 源码、配置文件与评测报告打包为 tar.gz，并附带逐文件 SHA-256 清单
+pub fn create(root: &Path, output: &Path) -> io::Result<Manifest> {
+    if !root.join(REPORT_NAME).exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "report.json not found, run `cargo run all` first",
+        ));
+    }
+
+    let mut paths = vec![PathBuf::from(CONFIG_NAME), PathBuf::from(REPORT_NAME)];
+    collect_sources(root, Path::new("exercises"), &mut paths)?;
+
+    let mut files = Vec::new();
+    for path in &paths {
+        files.push(FileEntry {
+            path: to_archive_path(path),
+            sha256: sha256_file(&root.join(path))?,
+            size: fs::metadata(root.join(path))?.len(),
+        });
+    }
+    let manifest = Manifest {
+        toolchain: toolchain_version()?,
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+        files,
+    };
+
+    let encoder = GzEncoder::new(File::create(output)?, Compression::default());
+    let mut archive = tar::Builder::new(encoder);
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest_json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(manifest.created_at);
+    header.set_cksum();
+    archive.append_data(&mut header, MANIFEST_NAME, manifest_json.as_slice())?;
+    for path in &paths {
+        archive.append_path_with_name(root.join(path), to_archive_path(path))?;
+    }
+    archive.into_inner()?.finish()?;
+    Ok(manifest)
+}
+
+// 解包到临时目录，校验清单中的哈希，再用评测程序在该目录下重新评测并与报告中声明的成绩对比。
+// official_config 为评测方自己的配置文件，提供时会覆盖包内的配置；参考实现总是取自当前目录下的 reference。
+pub fn verify(bundle: &Path, official_config: Option<&Path>) -> io::Result<Verification> {
+    let dir = temp_dir("verify")?;
+    let result = verify_in(bundle, official_config, &dir);
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+fn verify_in(
+    bundle: &Path,
+    official_config: Option<&Path>,
+    dir: &Path,
+) -> io::Result<Verification> {
+    tar::Archive::new(GzDecoder::new(File::open(bundle)?)).unpack(dir)?;
+    let manifest: Manifest = serde_json::from_reader(File::open(dir.join(MANIFEST_NAME))?)?;
+    let mut problems = Vec::new();
+
+    // 校验清单中的每个文件，并检查是否有清单外的文件
+    let mut listed = BTreeMap::new();
+    for entry in &manifest.files {
+        listed.insert(entry.path.clone(), entry);
+        match sha256_file(&dir.join(&entry.path)) {
+            Ok(hash) if hash == entry.sha256 => {}
+            Ok(_) => problems.push(format!("{}: SHA-256 mismatch", entry.path)),
+            Err(e) => problems.push(format!("{}: {}", entry.path, e)),
+        }
+    }
+    let mut unpacked = Vec::new();
+    collect_files(dir, Path::new(""), &mut unpacked)?;
+    for path in unpacked {
+        let path = to_archive_path(&path);
+        if path != MANIFEST_NAME && !listed.contains_key(&path) {
+            problems.push(format!("{}: not listed in the manifest", path));
+        }
+    }
+
+    let local_toolchain = toolchain_version()?;
+    if local_toolchain != manifest.toolchain {
+        eprintln!(
+            "Warning: bundle was graded with `{}`, verifying with `{}`",
+            manifest.toolchain, local_toolchain
+        );
+    }
+
+    if let Some(config) = official_config {
+        if sha256_file(config)? != sha256_file(&dir.join(CONFIG_NAME))? {
+            problems.push(format!("{}: differs from the official config", CONFIG_NAME));
+        }
+        fs::copy(config, dir.join(CONFIG_NAME))?;
+    }
+    let reference = dir.join(REFERENCE_DIR);
+    if reference.exists() {
+        fs::remove_dir_all(&reference)?;
+    }
+    if Path::new(REFERENCE_DIR).is_dir() {
+        copy_dir(Path::new(REFERENCE_DIR), &reference)?;
+    }
+
+    let claimed = schema::load_report(&dir.join(REPORT_NAME), None)?;
+    let regraded = regrade(dir)?;
+
+    let claimed_results: BTreeMap<&str, &ExerciseResult> = claimed
+        .exercises
+        .iter()
+        .map(|e| (e.name.as_str(), e))
+        .collect();
+    let mut timing_differences = Vec::new();
+    // 计时导致差异的习题不参与总分对比
+    let (mut claimed_total, mut regraded_total) = (
+        claimed.statistics.total_score,
+        regraded.statistics.total_score,
+    );
+    for exercise in &regraded.exercises {
+        match claimed_results.get(exercise.name.as_str()) {
+            Some(claimed)
+                if claimed.result == exercise.result && claimed.score == exercise.score => {}
+            Some(claimed) if probe_exceeded(claimed) || probe_exceeded(exercise) => {
+                claimed_total -= claimed.score;
+                regraded_total -= exercise.score;
+                timing_differences.push(format!(
+                    "{}: claimed {} point(s), regraded {} (scaling probe)",
+                    exercise.name, claimed.score, exercise.score
+                ));
+            }
+            Some(claimed) => problems.push(format!(
+                "{}: claimed {} point(s), regraded {}",
+                exercise.name, claimed.score, exercise.score
+            )),
+            None => problems.push(format!(
+                "{}: missing from the claimed report",
+                exercise.name
+            )),
+        }
+    }
+    if claimed_total != regraded_total {
+        problems.push(format!(
+            "total score: claimed {}, regraded {}",
+            claimed.statistics.total_score, regraded.statistics.total_score
+        ));
+    }
+
+    Ok(Verification {
+        manifest,
+        problems,
+        timing_differences,
+        claimed,
+        regraded,
+    })
+}
+
+// 复杂度探测依赖计时，机器繁忙时两次评测的结论可能不同
+fn probe_exceeded(exercise: &ExerciseResult) -> bool {
+    exercise
+        .complexity
+        .as_ref()
+        .is_some_and(|probe| probe.exceeded)
+}
+
+// 在独立进程中运行评测程序，避免与当前目录下的文件相互影响
+fn regrade(dir: &Path) -> io::Result<Report> {
+    let report_path = dir.join(REPORT_NAME);
+    fs::remove_file(&report_path)?;
+    let output = process::output_with_timeout(
+        Command::new(std::env::current_exe()?)
+            .arg("all")
+            .current_dir(dir),
+        REGRADE_TIMEOUT,
+    )
+    .map_err(|e| io::Error::new(e.kind(), format!("regrading failed: {}", e)))?;
+    if !report_path.exists() {
+        return Err(io::Error::other(format!(
+            "regrading produced no report: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    schema::load_report(&report_path, None)
+}
+
+fn collect_sources(root: &Path, relative: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries: Vec<_> = fs::read_dir(root.join(relative))?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let path = relative.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            if entry.file_name() != "target" {
+                collect_sources(root, &path, paths)?;
+            }
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| SOURCE_EXTENSIONS.contains(&ext))
+        {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &to.join(entry.file_name()))?;
+        } else {
+            fs::copy(entry.path(), to.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+fn collect_files(root: &Path, relative: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(root.join(relative))? {
+        let entry = entry?;
+        let path = relative.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            collect_files(root, &path, paths)?;
+        } else {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+// 包内统一使用 `/` 作为路径分隔符
+fn to_archive_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+pub fn sha256_file(path: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+pub fn toolchain_version() -> io::Result<String> {
+    let output = Command::new("rustc").arg("--version").output()?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
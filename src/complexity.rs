@@ -0,0 +1,161 @@
+use crate::harness::Harness;
+use crate::policy::Policy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+
+// 每个规模最多采样次数与时间预算（毫秒）
+const MAX_SAMPLES: usize = 31;
+const MIN_SAMPLES: usize = 3;
+const SAMPLE_BUDGET_MS: u64 = 300;
+
+// 复杂度探测配置（写在 exercise_config.json 中对应习题的 "probe" 字段）
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Probe {
+    // 根据规模 `n: usize` 生成输入的 Rust 表达式，结果绑定为 `input`
+    pub setup: String,
+    // 调用题解的 Rust 表达式，例如 "find_missing_number(input)"
+    pub call: String,
+    pub sizes: Vec<usize>,
+    // 声明的复杂度，例如 "O(n)"、"O(log n)"、"O(n^2)"
+    pub complexity: String,
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f64,
+    #[serde(default)]
+    pub policy: Policy,
+}
+
+fn default_tolerance() -> f64 {
+    0.5
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct Sample {
+    pub n: usize,
+    pub nanos: u64,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct ProbeResult {
+    pub declared: String,
+    pub measured_exponent: f64,
+    pub exceeded: bool,
+    pub samples: Vec<Sample>,
+}
+
+// 在逐渐增大的输入上运行题解，拟合 log(时间) ~ log(n) 的斜率，与声明复杂度的指数比较
+pub fn run(probe: &Probe, mut harness: Harness, workdir: &Path) -> io::Result<ProbeResult> {
+    let declared_exponent = declared_exponent(&probe.complexity).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unsupported complexity class: {}", probe.complexity),
+        )
+    })?;
+    if probe.sizes.len() < 2 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "a scaling probe needs at least two sizes",
+        ));
+    }
+
+    harness.prelude = format!(
+        "{}use std::hint::black_box;\nuse std::time::{{Duration, Instant}};\n",
+        harness.solution_imports()
+    );
+    harness.main_body = format!(
+        r#"
+    for &n in &{sizes:?} {{
+        let n: usize = n;
+        let mut samples: Vec<u128> = Vec::new();
+        let budget = Instant::now();
+        while samples.len() < {max} && (samples.len() < {min} || budget.elapsed() < Duration::from_millis({budget_ms})) {{
+            let input = black_box({setup});
+            let start = Instant::now();
+            black_box({call});
+            samples.push(start.elapsed().as_nanos());
+        }}
+        samples.sort_unstable();
+        println!("{{}} {{}}", n, samples[samples.len() / 2]);
+    }}"#,
+        sizes = probe.sizes,
+        max = MAX_SAMPLES,
+        min = MIN_SAMPLES,
+        budget_ms = SAMPLE_BUDGET_MS,
+        setup = probe.setup,
+        call = probe.call,
+    );
+
+    let output = harness.run(workdir)?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "scaling probe exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let samples: Vec<Sample> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (n, nanos) = line.split_once(' ')?;
+            Some(Sample {
+                n: n.parse().ok()?,
+                nanos: nanos.parse().ok()?,
+            })
+        })
+        .collect();
+
+    let measured_exponent = fit_exponent(&samples);
+    Ok(ProbeResult {
+        declared: probe.complexity.clone(),
+        measured_exponent,
+        exceeded: measured_exponent > declared_exponent + probe.tolerance,
+        samples,
+    })
+}
+
+// 复杂度类别对应的多项式指数，对数因子忽略不计（由容差吸收）
+fn declared_exponent(complexity: &str) -> Option<f64> {
+    let normalized: String = complexity
+        .to_lowercase()
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '*' && *c != '·')
+        .collect();
+    let inner = normalized.strip_prefix("o(")?.strip_suffix(')')?;
+    match inner {
+        "1" | "logn" => Some(0.0),
+        "sqrt(n)" | "√n" => Some(0.5),
+        "n" | "nlogn" => Some(1.0),
+        _ => {
+            let power = inner.strip_prefix("n^")?;
+            let power = power.strip_suffix("logn").unwrap_or(power);
+            power.parse().ok()
+        }
+    }
+}
+
+// 对 (ln n, ln t) 做最小二乘拟合，返回斜率
+fn fit_exponent(samples: &[Sample]) -> f64 {
+    let points: Vec<(f64, f64)> = samples
+        .iter()
+        .filter(|sample| sample.n > 0)
+        .map(|sample| ((sample.n as f64).ln(), (sample.nanos.max(1) as f64).ln()))
+        .collect();
+    if points.len() < 2 {
+        return 0.0;
+    }
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+    let covariance: f64 = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+    if variance == 0.0 {
+        0.0
+    } else {
+        covariance / variance
+    }
+}
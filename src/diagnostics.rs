@@ -0,0 +1,125 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::process::Command;
+
+// 终端中最多展示的错误条数
+const MAX_SHOWN_ERRORS: usize = 3;
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct Diagnostic {
+    pub level: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+}
+
+// rustc --error-format=json 输出的单条诊断（只保留需要的字段）
+#[derive(Deserialize)]
+struct RustcDiagnostic {
+    #[serde(rename = "$message_type", default)]
+    message_type: String,
+    message: String,
+    code: Option<RustcCode>,
+    level: String,
+    spans: Vec<RustcSpan>,
+}
+
+#[derive(Deserialize)]
+struct RustcCode {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct RustcSpan {
+    file_name: String,
+    line_start: usize,
+    column_start: usize,
+    is_primary: bool,
+}
+
+impl Diagnostic {
+    pub fn is_error(&self) -> bool {
+        self.level == "error"
+    }
+
+    // 形如 `easy/algorithm1.rs:12:5: error[E0308]: mismatched types`
+    pub fn summary(&self) -> String {
+        let location = match (&self.file, self.line, self.column) {
+            (Some(file), Some(line), Some(column)) => format!("{}:{}:{}: ", file, line, column),
+            (Some(file), _, _) => format!("{}: ", file),
+            _ => String::new(),
+        };
+        let code = match &self.code {
+            Some(code) => format!("[{}]", code),
+            None => String::new(),
+        };
+        format!("{}{}{}: {}", location, self.level, code, self.message)
+    }
+}
+
+// 解析 rustc 在 stderr 上逐行输出的 JSON 诊断，无法解析的行直接忽略
+// 只保留 error/warning，去掉 "aborting due to ..." 之类的收尾信息
+pub fn parse_rustc_diagnostics(stderr: &str) -> Vec<Diagnostic> {
+    stderr
+        .lines()
+        .filter_map(|line| serde_json::from_str::<RustcDiagnostic>(line).ok())
+        .filter(|diag| diag.message_type == "diagnostic" || diag.message_type.is_empty())
+        .filter(|diag| diag.level == "error" || diag.level == "warning")
+        .filter(|diag| !diag.message.starts_with("aborting due to"))
+        .map(|diag| {
+            let span = diag
+                .spans
+                .iter()
+                .find(|span| span.is_primary)
+                .or_else(|| diag.spans.first());
+            Diagnostic {
+                level: diag.level,
+                code: diag.code.map(|code| code.code),
+                message: diag.message,
+                file: span.map(|span| span.file_name.clone()),
+                line: span.map(|span| span.line_start),
+                column: span.map(|span| span.column_start),
+            }
+        })
+        .collect()
+}
+
+// 打印前几条错误的摘要
+pub fn print_error_summary(diagnostics: &[Diagnostic]) {
+    let errors: Vec<&Diagnostic> = diagnostics.iter().filter(|d| d.is_error()).collect();
+    for diag in errors.iter().take(MAX_SHOWN_ERRORS) {
+        eprintln!("  {}", diag.summary());
+    }
+    if errors.len() > MAX_SHOWN_ERRORS {
+        eprintln!(
+            "  ... and {} more error(s)",
+            errors.len() - MAX_SHOWN_ERRORS
+        );
+    }
+    if let Some(code) = errors.iter().find_map(|d| d.code.as_ref()) {
+        eprintln!(
+            "  Run `cargo run explain {}` for a detailed explanation.",
+            code
+        );
+    }
+}
+
+// 离线获取错误码的详细说明（rustc --explain）
+pub fn explain(code: &str) -> io::Result<String> {
+    let output = Command::new("rustc").arg("--explain").arg(code).output()?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
@@ -0,0 +1,319 @@
+use crate::harness::Harness;
+use crate::policy::Policy;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// 差分测试配置（写在 exercise_config.json 中对应习题的 "differential" 字段）
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Differential {
+    // 参考实现，路径相对于评测时的工作目录，例如 "reference/algorithm18.rs"。
+    // 参考实现放在 exercises 之外，不属于学生可修改的代码
+    pub reference: String,
+    // 使用 `g: &mut Gen` 生成一组输入的 Rust 表达式，结果绑定为 `input`
+    pub generator: String,
+    // 调用函数的 Rust 表达式，分别在题解和参考实现的作用域中求值，例如 "merge_intervals(input.clone())"
+    pub call: String,
+    #[serde(default = "default_cases")]
+    pub cases: usize,
+    #[serde(default = "default_seed")]
+    pub seed: u64,
+    #[serde(default)]
+    pub policy: Policy,
+}
+
+fn default_cases() -> usize {
+    2000
+}
+
+fn default_seed() -> u64 {
+    20250217
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct Counterexample {
+    pub case: usize,
+    pub input: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct DifferentialResult {
+    pub seed: u64,
+    pub cases: usize,
+    pub passed: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub counterexample: Option<Counterexample>,
+}
+
+// 生成的辅助程序中使用的随机输入生成器与收缩逻辑。
+// 生成器记录每次随机选择，收缩时删除或减小这些选择并重放生成器，因此任何基于 Gen 的生成表达式都能自动收缩。
+const GEN_SOURCE: &str = r#"
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::panic::{self, AssertUnwindSafe};
+
+pub struct Gen {
+    choices: Vec<u64>,
+    pos: usize,
+    replay: bool,
+    state: u64,
+}
+
+impl Gen {
+    fn random(state: u64) -> Gen {
+        Gen { choices: Vec::new(), pos: 0, replay: false, state }
+    }
+
+    fn replay(choices: Vec<u64>) -> Gen {
+        Gen { choices, pos: 0, replay: true, state: 0 }
+    }
+
+    // splitmix64
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    // 返回 [0, bound] 内的选择，0 是最“简单”的取值
+    fn draw(&mut self, bound: u64) -> u64 {
+        if self.replay {
+            let choice = self.choices.get(self.pos).copied().unwrap_or(0).min(bound);
+            self.pos += 1;
+            choice
+        } else {
+            let choice = match bound.checked_add(1) {
+                Some(modulus) => self.next_u64() % modulus,
+                None => self.next_u64(),
+            };
+            self.choices.push(choice);
+            choice
+        }
+    }
+
+    pub fn u64(&mut self, range: RangeInclusive<u64>) -> u64 {
+        let (lo, hi) = (*range.start(), *range.end());
+        lo + self.draw(hi - lo)
+    }
+
+    // 包含 0 的区间向 0 收缩，否则向靠近 0 的端点收缩
+    pub fn i64(&mut self, range: RangeInclusive<i64>) -> i64 {
+        let (lo, hi) = (*range.start(), *range.end());
+        if lo >= 0 {
+            lo + self.draw((hi - lo) as u64) as i64
+        } else if hi <= 0 {
+            hi - self.draw((hi - lo) as u64) as i64
+        } else if self.draw(1) == 0 {
+            self.draw(hi as u64) as i64
+        } else {
+            -(self.draw(lo.unsigned_abs()) as i64)
+        }
+    }
+
+    pub fn u32(&mut self, range: RangeInclusive<u32>) -> u32 {
+        self.u64(*range.start() as u64..=*range.end() as u64) as u32
+    }
+
+    pub fn i32(&mut self, range: RangeInclusive<i32>) -> i32 {
+        self.i64(*range.start() as i64..=*range.end() as i64) as i32
+    }
+
+    pub fn usize(&mut self, range: RangeInclusive<usize>) -> usize {
+        self.u64(*range.start() as u64..=*range.end() as u64) as usize
+    }
+
+    pub fn bool(&mut self) -> bool {
+        self.draw(1) == 1
+    }
+
+    pub fn choose<T: Clone>(&mut self, items: &[T]) -> T {
+        items[self.draw(items.len() as u64 - 1) as usize].clone()
+    }
+
+    pub fn vec<T>(&mut self, len: RangeInclusive<usize>, mut element: impl FnMut(&mut Gen) -> T) -> Vec<T> {
+        let len = self.usize(len);
+        (0..len).map(|_| element(self)).collect()
+    }
+
+    pub fn string(&mut self, len: RangeInclusive<usize>, alphabet: &str) -> String {
+        let alphabet: Vec<char> = alphabet.chars().collect();
+        self.vec(len, |g| g.choose(&alphabet)).into_iter().collect()
+    }
+}
+
+fn panic_message(payload: Box<dyn std::any::Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+// 参考实现 panic 的输入视为无效输入，返回 None
+fn compare<I, O: PartialEq + Debug>(
+    input: &I,
+    expected: &dyn Fn(&I) -> O,
+    actual: &dyn Fn(&I) -> O,
+) -> Option<(String, String)> {
+    let expected = panic::catch_unwind(AssertUnwindSafe(|| expected(input))).ok()?;
+    match panic::catch_unwind(AssertUnwindSafe(|| actual(input))) {
+        Ok(actual) if actual == expected => None,
+        Ok(actual) => Some((format!("{:?}", expected), format!("{:?}", actual))),
+        Err(payload) => Some((format!("{:?}", expected), format!("panicked: {}", panic_message(payload)))),
+    }
+}
+
+const SHRINK_BUDGET: usize = 5000;
+const SMALL_VALUES: u64 = 16;
+
+// 在保持不一致的前提下删除、减小选择序列
+fn shrink<I>(mut choices: Vec<u64>, generate: &dyn Fn(&mut Gen) -> I, fails: &dyn Fn(&I) -> bool) -> Vec<u64> {
+    let mut attempts = 0;
+    let still_fails = |candidate: &Vec<u64>, attempts: &mut usize| {
+        *attempts += 1;
+        let input = generate(&mut Gen::replay(candidate.clone()));
+        fails(&input)
+    };
+    let mut improved = true;
+    while improved && attempts < SHRINK_BUDGET {
+        improved = false;
+        for block in [8, 4, 2, 1] {
+            let mut start = 0;
+            while start + block <= choices.len() && attempts < SHRINK_BUDGET {
+                let mut candidate = choices.clone();
+                candidate.drain(start..start + block);
+                if still_fails(&candidate, &mut attempts) {
+                    choices = candidate;
+                    improved = true;
+                } else {
+                    start += 1;
+                }
+            }
+        }
+        for index in 0..choices.len() {
+            // 失败条件未必单调，先线性尝试最小的几个取值，再二分
+            for value in 0..choices[index].min(SMALL_VALUES) {
+                let mut candidate = choices.clone();
+                candidate[index] = value;
+                if still_fails(&candidate, &mut attempts) {
+                    choices = candidate;
+                    improved = true;
+                    break;
+                }
+            }
+            let (mut lo, mut hi) = (0, choices[index]);
+            while lo < hi && attempts < SHRINK_BUDGET {
+                let mid = lo + (hi - lo) / 2;
+                let mut candidate = choices.clone();
+                candidate[index] = mid;
+                if still_fails(&candidate, &mut attempts) {
+                    choices = candidate;
+                    hi = mid;
+                    improved = true;
+                } else {
+                    lo = mid + 1;
+                }
+            }
+        }
+    }
+    choices
+}
+
+fn run_differential<I: Debug, O: PartialEq + Debug>(
+    seed: u64,
+    cases: usize,
+    generate: impl Fn(&mut Gen) -> I,
+    expected: impl Fn(&I) -> O,
+    actual: impl Fn(&I) -> O,
+) {
+    panic::set_hook(Box::new(|_| {}));
+    let mut rng = Gen::random(seed);
+    for case in 0..cases {
+        let mut g = Gen::random(rng.next_u64());
+        let input = generate(&mut g);
+        if compare(&input, &expected, &actual).is_none() {
+            continue;
+        }
+        let fails = |input: &I| compare(input, &expected, &actual).is_some();
+        let choices = shrink(g.choices, &generate, &fails);
+        let input = generate(&mut Gen::replay(choices));
+        let (expected, actual) = compare(&input, &expected, &actual).expect("shrunk input must still fail");
+        println!("FAIL {}", case);
+        println!("input={:?}", input);
+        println!("expected={}", expected);
+        println!("actual={}", actual);
+        return;
+    }
+    println!("PASS {}", cases);
+}
+"#;
+
+// 用同一批随机输入分别运行题解与参考实现，找到不一致时收缩到最小的反例
+pub fn run(
+    differential: &Differential,
+    mut harness: Harness,
+    root: &Path,
+    workdir: &Path,
+) -> io::Result<DifferentialResult> {
+    let solution_imports = harness.solution_imports();
+    let reference = fs::canonicalize(root.join(&differential.reference))?;
+    harness.modules.push(("reference".to_string(), reference));
+    harness.prelude = GEN_SOURCE.to_string();
+    harness.main_body = format!(
+        r#"
+    run_differential(
+        {seed},
+        {cases},
+        |g: &mut Gen| {{ {generator} }},
+        |input| {{ use reference::*; {call} }},
+        |input| {{ {solution_imports} {call} }},
+    );"#,
+        seed = differential.seed,
+        cases = differential.cases,
+        generator = differential.generator,
+        call = differential.call,
+        solution_imports = solution_imports,
+    );
+
+    let output = harness.run(workdir)?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    let status = lines.next().unwrap_or_default();
+    if !output.status.success() || status.is_empty() {
+        return Err(io::Error::other(format!(
+            "differential test exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let counterexample = status.strip_prefix("FAIL ").map(|case| {
+        let mut field = |name: &str| {
+            lines
+                .next()
+                .and_then(|line| line.strip_prefix(name))
+                .unwrap_or_default()
+                .to_string()
+        };
+        Counterexample {
+            case: case.parse().unwrap_or_default(),
+            input: field("input="),
+            expected: field("expected="),
+            actual: field("actual="),
+        }
+    });
+    Ok(DifferentialResult {
+        seed: differential.seed,
+        cases: differential.cases,
+        passed: counterexample.is_none(),
+        counterexample,
+    })
+}
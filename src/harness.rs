@@ -0,0 +1,127 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// 由评测器生成的辅助程序：通过 #[path] 引入题解源码，再执行给定的 main 函数体
+pub struct Harness {
+    pub name: String,
+    pub modules: Vec<(String, PathBuf)>,
+    pub prelude: String,
+    pub main_body: String,
+}
+
+impl Harness {
+    // 以题解源码为模块构建辅助程序：单文件习题引入为 `solution`，Cargo 项目引入 src 下除 main.rs/tests.rs 外的所有模块
+    pub fn for_exercise(
+        name: &str,
+        exercise_path: &Path,
+        exercise_type: &str,
+    ) -> io::Result<Harness> {
+        let mut modules = Vec::new();
+        match exercise_type {
+            "single_file" => {
+                modules.push(("solution".to_string(), fs::canonicalize(exercise_path)?))
+            }
+            "cargo_project" => {
+                for entry in fs::read_dir(exercise_path.join("src"))? {
+                    let path = entry?.path();
+                    let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                        continue;
+                    };
+                    if path.extension().is_some_and(|ext| ext == "rs")
+                        && stem != "main"
+                        && stem != "tests"
+                    {
+                        modules.push((stem.to_string(), fs::canonicalize(&path)?));
+                    }
+                }
+                modules.sort();
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Unknown exercise type: {}", exercise_type),
+                ))
+            }
+        }
+        Ok(Harness {
+            name: name.to_string(),
+            modules,
+            prelude: String::new(),
+            main_body: String::new(),
+        })
+    }
+
+    // 将已引入的题解模块全部 glob 导入，供生成的代码直接调用题解函数
+    pub fn solution_imports(&self) -> String {
+        self.modules
+            .iter()
+            .map(|(module, _)| format!("#[allow(unused_imports)]\nuse {}::*;\n", module))
+            .collect()
+    }
+
+    fn source(&self) -> String {
+        let mut source = String::new();
+        for (module, path) in &self.modules {
+            source.push_str(&format!(
+                "#[allow(dead_code, unused_imports, unused_variables, unused_mut)]\n#[path = {:?}]\nmod {};\n",
+                path.display().to_string(),
+                module
+            ));
+        }
+        source.push_str(&self.prelude);
+        source.push_str("\nfn main() {\n");
+        source.push_str(&self.main_body);
+        source.push_str("\n}\n");
+        source
+    }
+
+    // 以 release 优化编译并在 workdir 下运行，返回运行输出；编译失败时返回编译器输出作为错误信息
+    pub fn run(&self, workdir: &Path) -> io::Result<Output> {
+        let dir = temp_dir(&self.name)?;
+        let result = self.build_and_run(&dir, workdir);
+        let _ = fs::remove_dir_all(&dir);
+        result
+    }
+
+    fn build_and_run(&self, dir: &Path, workdir: &Path) -> io::Result<Output> {
+        let main_file = dir.join("main.rs");
+        let binary = dir.join("harness");
+        fs::write(&main_file, self.source())?;
+
+        let compile = Command::new("rustc")
+            .arg("--edition=2021")
+            .arg("-O")
+            .arg(&main_file)
+            .arg("-o")
+            .arg(&binary)
+            .output()?;
+        if !compile.status.success() {
+            return Err(io::Error::other(format!(
+                "failed to compile {} harness:\n{}",
+                self.name,
+                String::from_utf8_lossy(&compile.stderr)
+            )));
+        }
+
+        Command::new(&binary).current_dir(workdir).output()
+    }
+}
+
+// 在系统临时目录下创建一个唯一的工作目录
+pub fn temp_dir(prefix: &str) -> io::Result<PathBuf> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    let dir = std::env::temp_dir().join(format!(
+        "cargotest-{}-{}-{}",
+        prefix.replace(|c: char| !c.is_ascii_alphanumeric(), "_"),
+        std::process::id(),
+        nanos
+    ));
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
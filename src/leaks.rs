@@ -0,0 +1,145 @@
+use crate::harness::temp_dir;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+// 链接进测试程序的计数分配器：记录尚未释放的分配次数与字节数，进程退出时输出到 stderr
+const COUNTING_ALLOCATOR: &str = r#"
+#[doc(hidden)]
+mod __leak_check {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+
+    static LIVE_ALLOCATIONS: AtomicI64 = AtomicI64::new(0);
+    static LIVE_BYTES: AtomicI64 = AtomicI64::new(0);
+    static REGISTERED: AtomicBool = AtomicBool::new(false);
+
+    extern "C" {
+        fn atexit(callback: extern "C" fn()) -> i32;
+    }
+
+    extern "C" fn report() {
+        eprintln!(
+            "LEAK-CHECK {} {}",
+            LIVE_ALLOCATIONS.load(Ordering::SeqCst),
+            LIVE_BYTES.load(Ordering::SeqCst)
+        );
+    }
+
+    struct Counting;
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            if !REGISTERED.swap(true, Ordering::SeqCst) {
+                atexit(report);
+            }
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                LIVE_ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+                LIVE_BYTES.fetch_add(layout.size() as i64, Ordering::SeqCst);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            LIVE_ALLOCATIONS.fetch_sub(1, Ordering::SeqCst);
+            LIVE_BYTES.fetch_sub(layout.size() as i64, Ordering::SeqCst);
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: Counting = Counting;
+}
+"#;
+
+// 用于校准的空测试：测试框架与标准库自身在退出时仍持有的分配不计为泄漏
+const CALIBRATION_SOURCE: &str = r#"
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn calibrate() {
+        let values = vec![1, 2, 3];
+        println!("{:?}", values);
+    }
+}
+"#;
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct LeakReport {
+    pub allocations: u64,
+    pub bytes: u64,
+}
+
+impl LeakReport {
+    pub fn is_clean(&self) -> bool {
+        self.allocations == 0
+    }
+}
+
+// 以包含计数分配器的方式重新编译单文件习题的测试并运行，返回退出时未释放的分配
+pub fn check(exercise_path: &Path) -> io::Result<LeakReport> {
+    let path = fs::canonicalize(exercise_path)?;
+    let (allocations, bytes) =
+        run_counted(&format!("include!({:?});\n", path.display().to_string()))?;
+    let (base_allocations, base_bytes) = baseline()?;
+    Ok(LeakReport {
+        allocations: (allocations - base_allocations).max(0) as u64,
+        bytes: (bytes - base_bytes).max(0) as u64,
+    })
+}
+
+fn baseline() -> io::Result<(i64, i64)> {
+    static BASELINE: OnceLock<Option<(i64, i64)>> = OnceLock::new();
+    BASELINE
+        .get_or_init(|| run_counted(CALIBRATION_SOURCE).ok())
+        .ok_or_else(|| io::Error::other("failed to calibrate the leak checker"))
+}
+
+fn run_counted(source: &str) -> io::Result<(i64, i64)> {
+    let dir = temp_dir("leaks")?;
+    let result = build_and_run(source, &dir);
+    let _ = fs::remove_dir_all(&dir);
+    result
+}
+
+fn build_and_run(source: &str, dir: &Path) -> io::Result<(i64, i64)> {
+    let main_file = dir.join("leak_check.rs");
+    let binary = dir.join("leak_check");
+    fs::write(&main_file, format!("{}{}", source, COUNTING_ALLOCATOR))?;
+
+    let compile = Command::new("rustc")
+        .arg("--test")
+        .arg(&main_file)
+        .arg("-o")
+        .arg(&binary)
+        .output()?;
+    if !compile.status.success() {
+        return Err(io::Error::other(format!(
+            "failed to compile the leak-checked tests:\n{}",
+            String::from_utf8_lossy(&compile.stderr)
+        )));
+    }
+
+    // 与普通评测一致，在当前目录下运行
+    let output = Command::new(&binary).output()?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "leak-checked tests failed: {}",
+            stderr.trim()
+        )));
+    }
+    stderr
+        .lines()
+        .filter_map(|line| line.strip_prefix("LEAK-CHECK "))
+        .find_map(|counts| {
+            let (allocations, bytes) = counts.split_once(' ')?;
+            Some((allocations.parse().ok()?, bytes.parse().ok()?))
+        })
+        .ok_or_else(|| io::Error::other("leak-checked tests produced no allocation report"))
+}
@@ -1,9 +1,42 @@
+mod aggregate;
+mod bench;
+mod bundle;
+mod complexity;
+mod diagnostics;
+mod differential;
+mod harness;
+mod leaks;
+mod metrics;
+mod policy;
+mod process;
+mod rules;
+mod schema;
+mod scoring;
+mod serve;
+mod source;
+mod tamper;
+mod toolchain;
+mod tui;
+
+use complexity::{Probe, ProbeResult};
+use diagnostics::Diagnostic;
+use differential::{Differential, DifferentialResult};
+use harness::Harness;
+use leaks::LeakReport;
+use metrics::Metrics;
+use policy::Policy;
+use rules::Rules;
+use schemars::JsonSchema;
+use scoring::{GradeSummary, Scoring};
 use serde::{Deserialize, Serialize};
+use source::SourceFile;
+use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io;
 use std::path::{Path, PathBuf};
 use std::process::{exit, Command};
 use std::time::Instant;
+use toolchain::ToolchainSummary;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct Exercise {
@@ -12,6 +45,17 @@ struct Exercise {
     #[serde(rename = "type")]
     exercise_type: String,
     score: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rules: Option<Rules>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    probe: Option<Probe>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    differential: Option<Differential>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    test_checksum: Option<String>,
+    // 未通过时在 TUI 中展示的提示
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hint: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -19,26 +63,76 @@ struct ExerciseConfig {
     easy: Vec<Exercise>,
     normal: Vec<Exercise>,
     hard: Vec<Exercise>,
+    #[serde(default)]
+    tamper_policy: Policy,
+    // 设置后用计数分配器检查单文件习题测试中的内存泄漏
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    leak_policy: Option<Policy>,
+    // 额外用于评测的工具链（如 MSRV 与最新 stable），结果只记录在报告的工具链矩阵中，不影响得分
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    toolchains: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scoring: Option<Scoring>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
 struct ExerciseResult {
     name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    difficulty: String,
     result: bool,
     score: i32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    diagnostics: Vec<Diagnostic>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    metrics: Option<Metrics>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    violations: Vec<Diagnostic>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    complexity: Option<ProbeResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    differential: Option<DifferentialResult>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    leaks: Option<LeakReport>,
+    // 工具链 -> 在该工具链上是否编译并通过测试
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    toolchains: BTreeMap<String, bool>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    tampered: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default)]
+struct Subtotal {
+    exercises: usize,
+    successes: usize,
+    failures: usize,
+    score: i32,
+    max_score: i32,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Default)]
 struct Statistics {
     total_exercises: usize,
     total_successes: usize,
     total_failures: usize,
     total_score: i32,
+    // 总耗时（秒，向下取整），保留给只读取该字段的旧脚本
     total_time: u64,
+    #[serde(default)]
+    total_time_ms: u64,
+    // 按难度（easy/normal/hard）的小计
+    #[serde(default)]
+    by_difficulty: BTreeMap<String, Subtotal>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    toolchains: Vec<ToolchainSummary>,
+    // 按计分策略计算的百分比与等级
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    grade: Option<GradeSummary>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
 struct Report {
+    schema_version: u32,
     exercises: Vec<ExerciseResult>,
     statistics: Statistics,
 }
@@ -46,15 +140,89 @@ struct Report {
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
-        eprintln!("Please provide a command: 'watch' or 'all'");
+        eprintln!(
+            "Please provide a command: 'watch', 'all [--min-score n]', 'seal', 'bundle [output]', 'verify <bundle>', 'serve [addr]', 'aggregate <dir> [--csv file]', 'schema [output]', 'tui', 'bench [exercise...] [--save] [--margin m]' or 'explain <code>'"
+        );
         exit(1);
     }
 
     let mode = &args[1];
+    if mode == "explain" {
+        run_explain(args.get(2));
+        return;
+    }
+    if mode == "seal" {
+        run_seal("exercise_config.json");
+        return;
+    }
+    if mode == "bundle" {
+        run_bundle(args.get(2).map_or("submission.tar.gz", |s| s.as_str()));
+        return;
+    }
+    if mode == "verify" {
+        run_verify(args.get(2));
+        return;
+    }
+    if mode == "tui" {
+        let result =
+            load_exercise_config("exercise_config.json").and_then(|config| tui::run(config));
+        if let Err(e) = result {
+            eprintln!("Dashboard failed: {}", e);
+            exit(1);
+        }
+        return;
+    }
+    if mode == "bench" {
+        run_bench(&args[2..]);
+        return;
+    }
+    if mode == "grade-one" {
+        run_grade_one(args.get(2), args.get(3));
+        return;
+    }
+    if mode == "schema" {
+        let output = args.get(2).map_or("report.schema.json", |s| s.as_str());
+        if let Err(e) = schema::write_report_schema(Path::new(output)) {
+            eprintln!("Failed to write {}: {}", output, e);
+            exit(1);
+        }
+        println!(
+            "Wrote report schema v{} to {}",
+            schema::SCHEMA_VERSION,
+            output
+        );
+        return;
+    }
+    if mode == "aggregate" {
+        run_aggregate(&args[2..]);
+        return;
+    }
+    if mode == "serve" {
+        let addr = args.get(2).map_or("127.0.0.1:8080", |s| s.as_str());
+        if let Err(e) = serve::serve(
+            addr,
+            Path::new("grading-store"),
+            Path::new("exercise_config.json"),
+        ) {
+            eprintln!("Grading server failed: {}", e);
+            exit(1);
+        }
+        return;
+    }
+    let min_score = match args.iter().position(|arg| arg == "--min-score") {
+        Some(index) => match args.get(index + 1).and_then(|s| s.parse::<f64>().ok()) {
+            Some(min_score) => Some(min_score),
+            None => {
+                eprintln!("--min-score expects a number, e.g. '--min-score 60'");
+                exit(1);
+            }
+        },
+        None => None,
+    };
     let start_time = Instant::now();
 
     // 加载 JSON 配置
-    let config = match load_exercise_config("exercise_config.json") {
+    let mut config = match load_exercise_config("exercise_config.json") {
         Ok(cfg) => cfg,
         Err(e) => {
             eprintln!("Failed to load config file: {}", e);
@@ -63,19 +231,16 @@ fn main() {
     };
 
     let mut report = Report {
+        schema_version: schema::SCHEMA_VERSION,
         exercises: Vec::new(),
-        statistics: Statistics {
-            total_exercises: 0,
-            total_successes: 0,
-            total_failures: 0,
-            total_score: 0,
-            total_time: 0,
-        },
+        statistics: Statistics::default(),
     };
 
-    evaluate_exercises_from_config(mode, config, &mut report);
+    evaluate_exercises_from_config(mode, &mut config, &mut report);
 
-    report.statistics.total_time = start_time.elapsed().as_secs();
+    let elapsed = start_time.elapsed();
+    report.statistics.total_time = elapsed.as_secs();
+    report.statistics.total_time_ms = elapsed.as_millis() as u64;
     report.statistics.total_exercises =
         report.statistics.total_successes + report.statistics.total_failures;
 
@@ -84,10 +249,38 @@ fn main() {
     println!("Total successes: {}", report.statistics.total_successes);
     println!("Total failures: {}", report.statistics.total_failures);
     println!("Total score: {}", report.statistics.total_score);
+    match scoring::grade(
+        &config,
+        &report.statistics.by_difficulty,
+        chrono::Local::now(),
+    ) {
+        Ok(grade) => {
+            print_grade(&grade);
+            report.statistics.grade = Some(grade);
+        }
+        Err(e) => eprintln!("Failed to apply the scoring policy: {}", e),
+    }
+    print_toolchain_matrix(&report);
 
     if let Err(e) = save_report_to_json("report.json", &report) {
         eprintln!("Error saving report: {}", e);
     }
+
+    // --min-score 以扣除迟交惩罚后的得分决定退出状态，便于在 CI 中使用
+    if let Some(min_score) = min_score {
+        let final_score = report
+            .statistics
+            .grade
+            .as_ref()
+            .map_or(report.statistics.total_score as f64, |g| g.final_score);
+        if final_score < min_score {
+            eprintln!(
+                "Score {:.1} is below the minimum of {:.1}",
+                final_score, min_score
+            );
+            exit(1);
+        }
+    }
 }
 
 fn load_exercise_config(file_path: &str) -> Result<ExerciseConfig, io::Error> {
@@ -96,55 +289,513 @@ fn load_exercise_config(file_path: &str) -> Result<ExerciseConfig, io::Error> {
     Ok(config)
 }
 
-fn evaluate_exercises_from_config(mode: &str, config: ExerciseConfig, report: &mut Report) {
-    let all_exercises = [config.easy, config.normal, config.hard].concat();
+fn save_exercise_config(file_path: &str, config: &ExerciseConfig) -> io::Result<()> {
+    let mut content = serde_json::to_string_pretty(config)?;
+    content.push('\n');
+    fs::write(file_path, content)
+}
 
-    for exercise in all_exercises {
-        println!("\nEvaluating {}: {}", exercise.exercise_type, exercise.name);
-        let result = evaluate_exercise(&exercise);
+fn evaluate_exercises_from_config(mode: &str, config: &mut ExerciseConfig, report: &mut Report) {
+    config.toolchains = toolchain::installed_only(&config.toolchains);
+    report.statistics.toolchains = config
+        .toolchains
+        .iter()
+        .map(|tc| ToolchainSummary {
+            toolchain: tc.clone(),
+            version: toolchain::version(tc),
+            successes: 0,
+            failures: 0,
+        })
+        .collect();
+    let all_exercises = [
+        ("easy", &config.easy),
+        ("normal", &config.normal),
+        ("hard", &config.hard),
+    ]
+    .into_iter()
+    .flat_map(|(difficulty, exercises)| exercises.iter().map(move |e| (difficulty, e)));
 
-        let score = if result { exercise.score } else { 0 };
+    for (difficulty, exercise) in all_exercises {
+        let exercise_result = grade_exercise(difficulty, exercise, config);
+        record_result(&mut report.statistics, &exercise_result, exercise.score);
+        report.exercises.push(exercise_result);
 
-        report.exercises.push(ExerciseResult {
-            name: exercise.name.clone(),
-            result,
-            score,
-        });
+        if mode == "watch" && !ask_to_continue() {
+            break;
+        }
+    }
+}
 
-        if result {
-            report.statistics.total_successes += 1;
-        } else {
-            report.statistics.total_failures += 1;
+// 评测单个习题：编译与测试、规则检查、复杂度探测与差分测试
+fn grade_exercise(
+    difficulty: &str,
+    exercise: &Exercise,
+    config: &ExerciseConfig,
+) -> ExerciseResult {
+    println!("\nEvaluating {}: {}", exercise.exercise_type, exercise.name);
+    let tampered = !verify_test_checksum(exercise);
+    let (mut result, mut diagnostics) = if tampered && config.tamper_policy == Policy::Fail {
+        (false, Vec::new())
+    } else {
+        evaluate_exercise(exercise, None)
+    };
+    let toolchains = run_toolchain_matrix(exercise, &config.toolchains);
+
+    let sources = load_sources(exercise);
+    let metrics = sources.as_deref().map(metrics::collect);
+    let violations = check_rules(exercise, sources.as_deref());
+    if !violations.is_empty() {
+        result = false;
+    }
+
+    let leaks = match config.leak_policy {
+        Some(policy) if result => run_leak_check(exercise, policy),
+        _ => None,
+    };
+    if let Some(leak_report) = &leaks {
+        if !leak_report.is_clean() && config.leak_policy == Some(Policy::Fail) {
+            result = false;
         }
+    }
 
-        report.statistics.total_score += score;
+    let mut complexity = None;
+    if let (true, Some(probe)) = (result, &exercise.probe) {
+        match run_probe(exercise, probe) {
+            Ok(probe_result) => {
+                if probe_result.exceeded && probe.policy == Policy::Fail {
+                    result = false;
+                }
+                complexity = Some(probe_result);
+            }
+            Err(e) => {
+                if probe.policy == Policy::Fail {
+                    result = false;
+                }
+                diagnostics.push(check_failure("scaling probe", &e, probe.policy));
+            }
+        }
+    }
 
-        if mode == "watch" && !ask_to_continue() {
-            break;
+    let mut differential = None;
+    if let (true, Some(config)) = (result, &exercise.differential) {
+        match run_differential(exercise, config) {
+            Ok(differential_result) => {
+                if !differential_result.passed && config.policy == Policy::Fail {
+                    result = false;
+                }
+                differential = Some(differential_result);
+            }
+            Err(e) => {
+                if config.policy == Policy::Fail {
+                    result = false;
+                }
+                diagnostics.push(check_failure("differential test", &e, config.policy));
+            }
         }
     }
+
+    ExerciseResult {
+        name: exercise.name.clone(),
+        difficulty: difficulty.to_string(),
+        result,
+        score: if result { exercise.score } else { 0 },
+        diagnostics,
+        metrics,
+        violations,
+        complexity,
+        differential,
+        leaks,
+        toolchains,
+        tampered,
+    }
 }
 
-fn evaluate_exercise(exercise: &Exercise) -> bool {
-    let exercise_path = PathBuf::from(&format!("./exercises/{}", exercise.path));
+fn record_result(statistics: &mut Statistics, exercise_result: &ExerciseResult, max_score: i32) {
+    if exercise_result.result {
+        statistics.total_successes += 1;
+    } else {
+        statistics.total_failures += 1;
+    }
+    statistics.total_score += exercise_result.score;
+
+    let subtotal = statistics
+        .by_difficulty
+        .entry(exercise_result.difficulty.clone())
+        .or_default();
+    subtotal.exercises += 1;
+    if exercise_result.result {
+        subtotal.successes += 1;
+    } else {
+        subtotal.failures += 1;
+    }
+    subtotal.score += exercise_result.score;
+    subtotal.max_score += max_score;
+
+    for summary in &mut statistics.toolchains {
+        match exercise_result.toolchains.get(&summary.toolchain) {
+            Some(true) => summary.successes += 1,
+            Some(false) => summary.failures += 1,
+            None => {}
+        }
+    }
+}
+
+// 只评测指定的习题，并将结果写入文件（供 TUI 在子进程中调用）
+fn run_grade_one(name: Option<&String>, output: Option<&String>) {
+    let (Some(name), Some(output)) = (name, output) else {
+        eprintln!("Usage: 'grade-one <exercise> <result file>'");
+        exit(1);
+    };
+    let mut config = match load_exercise_config("exercise_config.json") {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            eprintln!("Failed to load config file: {}", e);
+            exit(1);
+        }
+    };
+    config.toolchains = toolchain::installed_only(&config.toolchains);
+    let found = [
+        ("easy", &config.easy),
+        ("normal", &config.normal),
+        ("hard", &config.hard),
+    ]
+    .into_iter()
+    .find_map(|(difficulty, exercises)| {
+        exercises
+            .iter()
+            .find(|e| e.name == *name)
+            .map(|e| (difficulty, e))
+    });
+    let Some((difficulty, exercise)) = found else {
+        eprintln!("Unknown exercise: {}", name);
+        exit(1);
+    };
+    let exercise_result = grade_exercise(difficulty, exercise, &config);
+    let written = serde_json::to_string(&exercise_result)
+        .map_err(io::Error::from)
+        .and_then(|json| fs::write(output, json));
+    if let Err(e) = written {
+        eprintln!("Failed to write {}: {}", output, e);
+        exit(1);
+    }
+}
+
+fn exercise_path(exercise: &Exercise) -> PathBuf {
+    PathBuf::from(&format!("./exercises/{}", exercise.path))
+}
+
+fn evaluate_exercise(exercise: &Exercise, toolchain: Option<&str>) -> (bool, Vec<Diagnostic>) {
+    let exercise_path = exercise_path(exercise);
     match exercise.exercise_type.as_str() {
-        "single_file" => evaluate_single_file(&exercise_path),
-        "cargo_project" => evaluate_cargo_project(&exercise_path),
+        "single_file" => evaluate_single_file(&exercise_path, toolchain),
+        "cargo_project" => (
+            evaluate_cargo_project(&exercise_path, toolchain),
+            Vec::new(),
+        ),
         _ => {
             eprintln!("Unknown exercise type: {}", exercise.exercise_type);
-            false
+            (false, Vec::new())
+        }
+    }
+}
+
+// 解析题解源码，用于统计指标和检查规则
+fn load_sources(exercise: &Exercise) -> Option<Vec<SourceFile>> {
+    match source::load_solution_sources(&exercise_path(exercise), &exercise.exercise_type) {
+        Ok(sources) => Some(sources),
+        Err(e) => {
+            eprintln!("Failed to parse sources of {}: {}", exercise.name, e);
+            None
+        }
+    }
+}
+
+// 检查习题约束规则，源码无法解析时视为未通过
+fn check_rules(exercise: &Exercise, sources: Option<&[SourceFile]>) -> Vec<Diagnostic> {
+    let Some(rules) = &exercise.rules else {
+        return Vec::new();
+    };
+    let violations = match sources {
+        Some(sources) => rules::check(rules, sources),
+        None => vec![Diagnostic {
+            level: "error".to_string(),
+            code: None,
+            message: "sources could not be parsed, exercise rules cannot be verified".to_string(),
+            file: None,
+            line: None,
+            column: None,
+        }],
+    };
+    if !violations.is_empty() {
+        eprintln!("\x1b[31m{}: RULE VIOLATION\x1b[0m", exercise.name);
+        for violation in &violations {
+            eprintln!("  {}", violation.summary());
+        }
+    }
+    violations
+}
+
+// 校验受保护的测试代码是否被修改，未记录校验和的习题视为未修改
+fn verify_test_checksum(exercise: &Exercise) -> bool {
+    let Some(expected) = &exercise.test_checksum else {
+        return true;
+    };
+    let actual = match tamper::test_checksum(&exercise_path(exercise), &exercise.exercise_type) {
+        Ok(checksum) => checksum,
+        Err(e) => {
+            eprintln!("Failed to checksum tests of {}: {}", exercise.name, e);
+            String::new()
+        }
+    };
+    if actual == *expected {
+        return true;
+    }
+    eprintln!(
+        "\x1b[31m{}: TESTS MODIFIED (expected checksum {}, got {})\x1b[0m",
+        exercise.name,
+        expected,
+        if actual.is_empty() { "none" } else { &actual }
+    );
+    false
+}
+
+// 重新计算所有习题受保护测试代码的校验和并写回配置文件
+fn run_seal(config_path: &str) {
+    let mut config = match load_exercise_config(config_path) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            eprintln!("Failed to load config file: {}", e);
+            exit(1);
+        }
+    };
+    for exercise in config
+        .easy
+        .iter_mut()
+        .chain(config.normal.iter_mut())
+        .chain(config.hard.iter_mut())
+    {
+        match tamper::test_checksum(&exercise_path(exercise), &exercise.exercise_type) {
+            Ok(checksum) => {
+                println!("{}: {}", exercise.name, checksum);
+                exercise.test_checksum = Some(checksum);
+            }
+            Err(e) => {
+                eprintln!("Failed to checksum tests of {}: {}", exercise.name, e);
+                exit(1);
+            }
+        }
+    }
+    if let Err(e) = save_exercise_config(config_path, &config) {
+        eprintln!("Failed to save config file: {}", e);
+        exit(1);
+    }
+}
+
+// Cargo 项目的辅助程序在项目目录下运行（题解可能读取相对路径的数据文件）
+fn exercise_workdir(exercise: &Exercise) -> PathBuf {
+    match exercise.exercise_type.as_str() {
+        "cargo_project" => exercise_path(exercise),
+        _ => PathBuf::from("."),
+    }
+}
+
+fn exercise_harness(exercise: &Exercise) -> io::Result<Harness> {
+    Harness::for_exercise(
+        &exercise.name,
+        &exercise_path(exercise),
+        &exercise.exercise_type,
+    )
+}
+
+// 差分测试：用随机输入对比题解与参考实现
+fn run_differential(
+    exercise: &Exercise,
+    differential: &Differential,
+) -> io::Result<DifferentialResult> {
+    let result = exercise_harness(exercise).and_then(|harness| {
+        differential::run(
+            differential,
+            harness,
+            Path::new("."),
+            &exercise_workdir(exercise),
+        )
+    });
+    match result {
+        Ok(differential_result) => {
+            match &differential_result.counterexample {
+                Some(counterexample) => {
+                    let color = if differential.policy == Policy::Fail {
+                        31
+                    } else {
+                        33
+                    };
+                    eprintln!(
+                        "\x1b[{}m{}: DIFFERENTIAL TEST FAILED (seed {}, case {})\x1b[0m",
+                        color, exercise.name, differential_result.seed, counterexample.case
+                    );
+                    eprintln!("  input:    {}", counterexample.input);
+                    eprintln!("  expected: {}", counterexample.expected);
+                    eprintln!("  actual:   {}", counterexample.actual);
+                }
+                None => println!(
+                    "{}: {} randomized cases match the reference",
+                    exercise.name, differential_result.cases
+                ),
+            }
+            Ok(differential_result)
+        }
+        Err(e) => {
+            eprintln!(
+                "Failed to run differential test for {}: {}",
+                exercise.name, e
+            );
+            Err(e)
+        }
+    }
+}
+
+fn print_grade(grade: &GradeSummary) {
+    if grade.late_days > 0 {
+        println!(
+            "Late penalty: {} day(s) late, -{:.0}%",
+            grade.late_days, grade.penalty
+        );
+    }
+    println!(
+        "Final score: {:.1}/{} ({:.1}%)",
+        grade.final_score, grade.max_score, grade.percentage
+    );
+    if let Some(letter) = &grade.grade {
+        println!("Grade: {}", letter);
+    }
+    match grade.passed {
+        Some(true) => println!("\x1b[32mPassed\x1b[0m"),
+        Some(false) => println!("\x1b[31mBelow the passing score\x1b[0m"),
+        None => {}
+    }
+}
+
+// 打印各习题在每个工具链上的结果
+fn print_toolchain_matrix(report: &Report) {
+    let toolchains = &report.statistics.toolchains;
+    if toolchains.is_empty() {
+        return;
+    }
+    println!("\nToolchain matrix:");
+    for summary in toolchains {
+        println!(
+            "  {}: {} passed, {} failed ({})",
+            summary.toolchain, summary.successes, summary.failures, summary.version
+        );
+    }
+    print!("  {:<16}", "");
+    for summary in toolchains {
+        print!(" {:>10}", summary.toolchain);
+    }
+    println!();
+    for exercise in &report.exercises {
+        print!("  {:<16}", exercise.name);
+        for summary in toolchains {
+            let cell = match exercise.toolchains.get(&summary.toolchain) {
+                Some(true) => "\x1b[32m      pass\x1b[0m",
+                Some(false) => "\x1b[31m      FAIL\x1b[0m",
+                None => "         -",
+            };
+            print!(" {}", cell);
+        }
+        println!();
+    }
+}
+
+// 在配置的其他工具链上编译并测试，生成工具链矩阵的一行
+fn run_toolchain_matrix(exercise: &Exercise, toolchains: &[String]) -> BTreeMap<String, bool> {
+    toolchains
+        .iter()
+        .map(|tc| {
+            println!("Checking {} on toolchain {}", exercise.name, tc);
+            let (passed, _) = evaluate_exercise(exercise, Some(tc));
+            (tc.clone(), passed)
+        })
+        .collect()
+}
+
+// 内存泄漏检查：仅适用于单文件习题，测试通过后以计数分配器重新编译运行
+fn run_leak_check(exercise: &Exercise, policy: Policy) -> Option<LeakReport> {
+    if exercise.exercise_type != "single_file" {
+        return None;
+    }
+    match leaks::check(&exercise_path(exercise)) {
+        Ok(leak_report) => {
+            if leak_report.is_clean() {
+                println!("{}: no memory leaked by the tests", exercise.name);
+            } else {
+                let color = if policy == Policy::Fail { 31 } else { 33 };
+                eprintln!(
+                    "\x1b[{}m{}: MEMORY LEAK ({} allocation(s), {} byte(s) never freed)\x1b[0m",
+                    color, exercise.name, leak_report.allocations, leak_report.bytes
+                );
+            }
+            Some(leak_report)
+        }
+        Err(e) => {
+            eprintln!("Failed to check {} for leaks: {}", exercise.name, e);
+            None
+        }
+    }
+}
+
+// 检查本身无法运行时记入诊断信息：fail 策略下为 error（习题判定为不通过），warn 策略下为 warning
+fn check_failure(check: &str, error: &io::Error, policy: Policy) -> Diagnostic {
+    Diagnostic {
+        level: match policy {
+            Policy::Fail => "error",
+            Policy::Warn => "warning",
+        }
+        .to_string(),
+        code: None,
+        message: format!("{} could not run: {}", check, error),
+        file: None,
+        line: None,
+        column: None,
+    }
+}
+
+// 复杂度探测：在逐渐增大的输入上计时，检查增长是否超过声明的复杂度
+fn run_probe(exercise: &Exercise, probe: &Probe) -> io::Result<ProbeResult> {
+    let result = exercise_harness(exercise)
+        .and_then(|harness| complexity::run(probe, harness, &exercise_workdir(exercise)));
+    match result {
+        Ok(probe_result) => {
+            if probe_result.exceeded {
+                let color = if probe.policy == Policy::Fail { 31 } else { 33 };
+                eprintln!(
+                    "\x1b[{}m{}: measured growth ~n^{:.2} exceeds declared {}\x1b[0m",
+                    color, exercise.name, probe_result.measured_exponent, probe_result.declared
+                );
+            } else {
+                println!(
+                    "{}: measured growth ~n^{:.2} within declared {}",
+                    exercise.name, probe_result.measured_exponent, probe_result.declared
+                );
+            }
+            Ok(probe_result)
+        }
+        Err(e) => {
+            eprintln!("Failed to run scaling probe for {}: {}", exercise.name, e);
+            Err(e)
         }
     }
 }
 
 // 评测单文件 Rust 习题（使用 rustc --test 并执行测试）
-fn evaluate_single_file(file_path: &PathBuf) -> bool {
+fn evaluate_single_file(file_path: &PathBuf, toolchain: Option<&str>) -> (bool, Vec<Diagnostic>) {
     // 获取文件名（不带扩展名）
     let test_binary = file_path.with_extension("");
 
     // 编译测试文件
-    let compile_output = Command::new("rustc")
+    let compile_output = toolchain::command("rustc", toolchain)
         .arg("--test") // 使用 rustc --test 进行编译
+        .arg("--error-format=json") // 输出结构化诊断信息
         .arg(file_path)
         .arg("-o")
         .arg(&test_binary) // 指定输出文件
@@ -162,6 +813,7 @@ fn evaluate_single_file(file_path: &PathBuf) -> bool {
                         true
                     } else {
                         println!("\x1b[31m{}: TEST FAILED\x1b[0m", file_path.display());
+                        print!("{}", String::from_utf8_lossy(&test_run.stdout));
                         false
                     }
                 }
@@ -185,23 +837,26 @@ fn evaluate_single_file(file_path: &PathBuf) -> bool {
                 );
             }
 
-            test_passed
+            (test_passed, Vec::new())
         } else {
             // 编译失败
+            let diagnostics =
+                diagnostics::parse_rustc_diagnostics(&String::from_utf8_lossy(&output.stderr));
             eprintln!("\x1b[31m{}: COMPILATION FAILED\x1b[0m", file_path.display());
-            false
+            diagnostics::print_error_summary(&diagnostics);
+            (false, diagnostics)
         }
     } else {
         eprintln!("Error executing rustc --test for {}", file_path.display());
-        false
+        (false, Vec::new())
     }
 }
 
 // 评测 Cargo 项目
-fn evaluate_cargo_project(proj_path: &PathBuf) -> bool {
-    let build_success = run_cargo_command(proj_path, "build");
-    let test_success = run_cargo_command(proj_path, "test");
-    let clippy_success = run_cargo_command(proj_path, "clippy");
+fn evaluate_cargo_project(proj_path: &PathBuf, toolchain: Option<&str>) -> bool {
+    let build_success = run_cargo_command(proj_path, "build", toolchain);
+    let test_success = run_cargo_command(proj_path, "test", toolchain);
+    let clippy_success = run_cargo_command(proj_path, "clippy", toolchain);
 
     let passed = build_success && test_success && clippy_success;
 
@@ -217,14 +872,20 @@ fn evaluate_cargo_project(proj_path: &PathBuf) -> bool {
 }
 
 // 运行 Cargo 命令
-fn run_cargo_command(proj_path: &PathBuf, command: &str) -> bool {
-    let output = Command::new("cargo")
+fn run_cargo_command(proj_path: &PathBuf, command: &str, toolchain: Option<&str>) -> bool {
+    let output = toolchain::command("cargo", toolchain)
         .arg(command)
         .current_dir(proj_path)
         .output();
 
     match output {
-        Ok(out) => out.status.success(),
+        Ok(out) if out.status.success() => true,
+        Ok(out) => {
+            println!("`cargo {}` failed in {}:", command, proj_path.display());
+            print!("{}", String::from_utf8_lossy(&out.stdout));
+            eprint!("{}", String::from_utf8_lossy(&out.stderr));
+            false
+        }
         Err(_) => false,
     }
 }
@@ -245,6 +906,240 @@ fn clean_target_directory(proj_path: &Path) {
     }
 }
 
+// 打包离线提交
+fn run_bundle(output: &str) {
+    match bundle::create(Path::new("."), Path::new(output)) {
+        Ok(manifest) => println!(
+            "Bundled {} files into {} ({})",
+            manifest.files.len(),
+            output,
+            manifest.toolchain
+        ),
+        Err(e) => {
+            eprintln!("Failed to create bundle: {}", e);
+            exit(1);
+        }
+    }
+}
+
+// 校验离线提交并重新评测，声明的成绩与重新评测结果不一致时以非零状态退出
+fn run_verify(bundle_path: Option<&String>) {
+    let Some(bundle_path) = bundle_path else {
+        eprintln!("Please provide a bundle, e.g. 'verify submission.tar.gz'");
+        exit(1);
+    };
+    let official_config = Path::new("exercise_config.json");
+    let official_config = official_config.exists().then_some(official_config);
+    let verification = match bundle::verify(Path::new(bundle_path), official_config) {
+        Ok(verification) => verification,
+        Err(e) => {
+            eprintln!("Failed to verify {}: {}", bundle_path, e);
+            exit(1);
+        }
+    };
+
+    println!("Bundle: {}", bundle_path);
+    println!("Files: {}", verification.manifest.files.len());
+    println!("Toolchain: {}", verification.manifest.toolchain);
+    println!(
+        "Claimed score: {}",
+        verification.claimed.statistics.total_score
+    );
+    println!(
+        "Regraded score: {}",
+        verification.regraded.statistics.total_score
+    );
+    if !verification.timing_differences.is_empty() {
+        println!("\x1b[33mTiming-dependent differences (not counted):\x1b[0m");
+        for difference in &verification.timing_differences {
+            println!("  {}", difference);
+        }
+    }
+    if verification.is_consistent() {
+        println!("\x1b[32mBundle verified: claimed scores confirmed\x1b[0m");
+    } else {
+        println!("\x1b[31mBundle verification failed:\x1b[0m");
+        for problem in &verification.problems {
+            println!("  {}", problem);
+        }
+        exit(1);
+    }
+}
+
+// 对测试代码中有计时的习题做多次计时，与测试中的时间上限及保存的基线比较
+fn run_bench(args: &[String]) {
+    const BASELINE: &str = "bench-baseline.json";
+    let mut names = Vec::new();
+    let mut save = false;
+    let mut margin = 0.2;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--save" => save = true,
+            "--margin" => match args.next().and_then(|m| m.parse::<f64>().ok()) {
+                Some(m) if (0.0..1.0).contains(&m) => margin = m,
+                _ => {
+                    eprintln!("--margin expects a fraction between 0 and 1, e.g. 0.2");
+                    exit(1);
+                }
+            },
+            name => names.push(name.to_string()),
+        }
+    }
+    let config = match load_exercise_config("exercise_config.json") {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            eprintln!("Failed to load config file: {}", e);
+            exit(1);
+        }
+    };
+    let baseline = bench::load_baseline(Path::new(BASELINE)).unwrap_or_default();
+
+    let mut cases = Vec::new();
+    let mut failed = false;
+    for exercise in [config.easy, config.normal, config.hard].concat() {
+        if !names.is_empty() && !names.contains(&exercise.name) {
+            continue;
+        }
+        let path = exercise_path(&exercise);
+        let run = exercise_harness(&exercise).and_then(|harness| {
+            bench::run(
+                &exercise.name,
+                &path,
+                &exercise.exercise_type,
+                harness,
+                &exercise_workdir(&exercise),
+            )
+        });
+        let run = match run {
+            Ok(run) => run,
+            Err(e) => {
+                eprintln!("\x1b[31m{}: benchmark failed: {}\x1b[0m", exercise.name, e);
+                failed = true;
+                continue;
+            }
+        };
+        if run.cases.is_empty() {
+            continue;
+        }
+
+        println!("\n{}:", exercise.name);
+        for case in &run.cases {
+            let verdict = case.verdict(margin);
+            let (label, color) = match verdict {
+                bench::Verdict::Pass => ("ok", 32),
+                bench::Verdict::Marginal => ("marginal", 33),
+                bench::Verdict::Slow => ("too slow", 31),
+                bench::Verdict::Unbounded => ("no limit", 0),
+            };
+            let limit = case.threshold_nanos.map_or("-".to_string(), format_nanos);
+            print!(
+                "  {} #{:<3} median {:>10}  p95 {:>10}  stddev {:>10}  limit {:>8}  ({} runs) \x1b[{}m{}\x1b[0m",
+                case.test,
+                case.case,
+                format_nanos(case.median_nanos),
+                format_nanos(case.p95_nanos),
+                format_nanos(case.stddev_nanos),
+                limit,
+                case.runs,
+                color,
+                label
+            );
+            if let Some(base) = baseline.iter().find(|b| b.key() == case.key()) {
+                let change =
+                    (case.median_nanos as f64 / base.median_nanos.max(1) as f64 - 1.0) * 100.0;
+                if case.regressed_from(base, margin) {
+                    print!("  \x1b[31mregressed {:+.0}% vs baseline\x1b[0m", change);
+                    failed = true;
+                } else {
+                    print!("  {:+.0}% vs baseline", change);
+                }
+            }
+            println!();
+            failed |= verdict == bench::Verdict::Slow;
+        }
+        for (test, message) in &run.failures {
+            eprintln!("\x1b[31m  {} failed: {}\x1b[0m", test, message);
+            failed = true;
+        }
+        cases.extend(run.cases);
+    }
+
+    if save {
+        match bench::save_baseline(Path::new(BASELINE), &cases) {
+            Ok(()) => println!("\nSaved {} case(s) to {}", cases.len(), BASELINE),
+            Err(e) => {
+                eprintln!("Failed to save {}: {}", BASELINE, e);
+                exit(1);
+            }
+        }
+    }
+    if failed {
+        exit(1);
+    }
+}
+
+fn format_nanos(nanos: u64) -> String {
+    if nanos >= 1_000_000_000 {
+        format!("{:.2}s", nanos as f64 / 1e9)
+    } else if nanos >= 1_000_000 {
+        format!("{:.2}ms", nanos as f64 / 1e6)
+    } else {
+        format!("{:.2}µs", nanos as f64 / 1e3)
+    }
+}
+
+// 汇总多份评测报告，输出排行榜与各题通过率，可选导出 CSV
+fn run_aggregate(args: &[String]) {
+    let mut dir = None;
+    let mut csv = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--csv" {
+            csv = args.next();
+        } else {
+            dir = Some(arg);
+        }
+    }
+    let Some(dir) = dir else {
+        eprintln!(
+            "Please provide a directory of reports, e.g. 'aggregate reports --csv ranking.csv'"
+        );
+        exit(1);
+    };
+    let config = load_exercise_config("exercise_config.json").ok();
+    let aggregate = match aggregate::aggregate(Path::new(dir), config.as_ref()) {
+        Ok(aggregate) => aggregate,
+        Err(e) => {
+            eprintln!("Failed to read reports from {}: {}", dir, e);
+            exit(1);
+        }
+    };
+    aggregate.print();
+    if let Some(csv) = csv {
+        if let Err(e) = aggregate.write_csv(Path::new(csv)) {
+            eprintln!("Failed to write {}: {}", csv, e);
+            exit(1);
+        }
+        println!("\nWrote {}", csv);
+    }
+}
+
+// 离线查看错误码说明
+fn run_explain(code: Option<&String>) {
+    let Some(code) = code else {
+        eprintln!("Please provide an error code, e.g. 'explain E0308'");
+        exit(1);
+    };
+    match diagnostics::explain(code) {
+        Ok(text) => println!("{}", text),
+        Err(e) => {
+            eprintln!("Failed to explain {}: {}", code, e);
+            exit(1);
+        }
+    }
+}
+
 // 用户确认是否继续
 fn ask_to_continue() -> bool {
     let mut input = String::new();
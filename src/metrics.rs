@@ -0,0 +1,121 @@
+use crate::source::SourceFile;
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use syn::visit::{self, Visit};
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct Metrics {
+    pub lines_of_code: usize,
+    pub unsafe_blocks: usize,
+    pub clone_calls: usize,
+    pub unwrap_calls: usize,
+    pub max_nesting_depth: usize,
+}
+
+// 统计题解源码（不含测试代码）的静态指标
+pub fn collect(sources: &[SourceFile]) -> Metrics {
+    let mut metrics = Metrics::default();
+    for source in sources {
+        metrics.lines_of_code += lines_of_code(source);
+
+        let mut visitor = MetricsVisitor::default();
+        for item in source.solution_items() {
+            visitor.visit_item(item);
+        }
+        metrics.unsafe_blocks += visitor.unsafe_blocks;
+        metrics.clone_calls += visitor.clone_calls;
+        metrics.unwrap_calls += visitor.unwrap_calls;
+        metrics.max_nesting_depth = metrics.max_nesting_depth.max(visitor.max_depth);
+    }
+    metrics
+}
+
+// 代码行数：含有至少一个 token 的行，注释、文档注释、空行和测试代码不计入
+fn lines_of_code(source: &SourceFile) -> usize {
+    let Ok(tokens) = source.text.parse::<TokenStream>() else {
+        return 0;
+    };
+    let mut lines = BTreeSet::new();
+    collect_token_lines(tokens, &mut lines);
+
+    let test_ranges = source.test_line_ranges();
+    lines
+        .into_iter()
+        .filter(|line| {
+            !test_ranges
+                .iter()
+                .any(|(start, end)| start <= line && line <= end)
+        })
+        .count()
+}
+
+fn collect_token_lines(tokens: TokenStream, lines: &mut BTreeSet<usize>) {
+    let mut iter = tokens.into_iter().peekable();
+    while let Some(token) = iter.next() {
+        match token {
+            // 跳过 `#[doc = "..."]` / `#![doc = "..."]`（即文档注释）
+            TokenTree::Punct(punct) if punct.as_char() == '#' => {
+                let mut lookahead = iter.clone();
+                if matches!(lookahead.peek(), Some(TokenTree::Punct(bang)) if bang.as_char() == '!')
+                {
+                    lookahead.next();
+                }
+                if matches!(lookahead.next(), Some(TokenTree::Group(group)) if is_doc_attr(&group))
+                {
+                    iter = lookahead;
+                    continue;
+                }
+                lines.insert(punct.span().start().line);
+            }
+            TokenTree::Group(group) => {
+                lines.insert(group.span_open().start().line);
+                lines.insert(group.span_close().start().line);
+                collect_token_lines(group.stream(), lines);
+            }
+            other => {
+                let span = other.span();
+                lines.extend(span.start().line..=span.end().line);
+            }
+        }
+    }
+}
+
+fn is_doc_attr(group: &proc_macro2::Group) -> bool {
+    group.delimiter() == Delimiter::Bracket
+        && matches!(group.stream().into_iter().next(), Some(TokenTree::Ident(ident)) if ident == "doc")
+}
+
+#[derive(Default)]
+struct MetricsVisitor {
+    unsafe_blocks: usize,
+    clone_calls: usize,
+    unwrap_calls: usize,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl<'ast> Visit<'ast> for MetricsVisitor {
+    // 函数体算第 1 层，每多一层代码块加 1
+    fn visit_block(&mut self, block: &'ast syn::Block) {
+        self.depth += 1;
+        self.max_depth = self.max_depth.max(self.depth);
+        visit::visit_block(self, block);
+        self.depth -= 1;
+    }
+
+    fn visit_expr_unsafe(&mut self, expr: &'ast syn::ExprUnsafe) {
+        self.unsafe_blocks += 1;
+        visit::visit_expr_unsafe(self, expr);
+    }
+
+    fn visit_expr_method_call(&mut self, expr: &'ast syn::ExprMethodCall) {
+        if expr.method == "clone" {
+            self.clone_calls += 1;
+        } else if expr.method == "unwrap" {
+            self.unwrap_calls += 1;
+        }
+        visit::visit_expr_method_call(self, expr);
+    }
+}
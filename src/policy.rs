@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+// 检查发现问题时的处理方式：warn 仅提示并记录在报告中，fail 判定习题不通过
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Policy {
+    #[default]
+    Warn,
+    Fail,
+}
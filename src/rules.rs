@@ -0,0 +1,268 @@
+use crate::diagnostics::Diagnostic;
+use crate::source::SourceFile;
+use proc_macro2::Span;
+use serde::{Deserialize, Serialize};
+use syn::parse::ParseStream;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::visit::{self, Visit};
+
+// 习题约束规则（写在 exercise_config.json 中对应习题的 "rules" 字段）
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Rules {
+    // 禁用的运算符，例如 "+"、"+="
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub banned_operators: Vec<String>,
+    // 禁用的类型，例如 "HashMap"、"HashSet"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub banned_types: Vec<String>,
+    // 禁用的函数或方法，例如 "Vec::new"、"mem::swap"、"sort"。
+    // 多段路径须完整出现（"Vec::new" 匹配 `Vec::new`、`std::vec::Vec::new`）；
+    // 单段名称只在调用处匹配（`sort(..)`、`x.sort()`），同名的变量、字段或常量不受影响
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub banned_functions: Vec<String>,
+    // 禁用的宏，例如 "vec"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub banned_macros: Vec<String>,
+    // 禁用的 crate 或模块路径前缀，例如 "std::collections"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub banned_crates: Vec<String>,
+    // 违反规则时附带的说明
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+// 检查题解代码（不含测试代码）是否违反规则
+pub fn check(rules: &Rules, sources: &[SourceFile]) -> Vec<Diagnostic> {
+    let mut violations = Vec::new();
+    for source in sources {
+        let mut checker = RuleChecker {
+            rules,
+            source,
+            violations: &mut violations,
+        };
+        for item in source.solution_items() {
+            checker.visit_item(item);
+        }
+    }
+    violations
+}
+
+struct RuleChecker<'a> {
+    rules: &'a Rules,
+    source: &'a SourceFile,
+    violations: &'a mut Vec<Diagnostic>,
+}
+
+impl RuleChecker<'_> {
+    fn report(&mut self, span: Span, what: String) {
+        let message = match &self.rules.reason {
+            Some(reason) => format!("{} is not allowed in this exercise ({})", what, reason),
+            None => format!("{} is not allowed in this exercise", what),
+        };
+        self.violations.push(Diagnostic {
+            level: "error".to_string(),
+            code: None,
+            message,
+            file: Some(self.source.path.display().to_string()),
+            line: Some(span.start().line),
+            column: Some(span.start().column + 1),
+        });
+    }
+
+    fn check_segments(&mut self, span: Span, segments: &[String]) {
+        for banned in &self.rules.banned_types {
+            if segments.iter().any(|segment| segment == banned) {
+                self.report(span, format!("type `{}`", banned));
+            }
+        }
+        for banned in &self.rules.banned_functions {
+            let banned_segments = split_path(banned);
+            if banned_segments.len() > 1 && segments.ends_with(&banned_segments) {
+                self.report(span, format!("function `{}`", banned));
+            }
+        }
+        for banned in &self.rules.banned_crates {
+            if segments.starts_with(&split_path(banned)) {
+                self.report(span, format!("`{}`", banned));
+            }
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for RuleChecker<'_> {
+    fn visit_bin_op(&mut self, op: &'ast syn::BinOp) {
+        let symbol = bin_op_symbol(op);
+        if self
+            .rules
+            .banned_operators
+            .iter()
+            .any(|banned| banned == symbol)
+        {
+            self.report(op.span(), format!("operator `{}`", symbol));
+        }
+        visit::visit_bin_op(self, op);
+    }
+
+    fn visit_un_op(&mut self, op: &'ast syn::UnOp) {
+        let symbol = match op {
+            syn::UnOp::Deref(_) => "*",
+            syn::UnOp::Not(_) => "!",
+            syn::UnOp::Neg(_) => "-",
+            _ => "",
+        };
+        if self
+            .rules
+            .banned_operators
+            .iter()
+            .any(|banned| banned == symbol)
+        {
+            self.report(op.span(), format!("unary operator `{}`", symbol));
+        }
+        visit::visit_un_op(self, op);
+    }
+
+    fn visit_path(&mut self, path: &'ast syn::Path) {
+        let segments: Vec<String> = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect();
+        self.check_segments(path.span(), &segments);
+        visit::visit_path(self, path);
+    }
+
+    fn visit_item_use(&mut self, item: &'ast syn::ItemUse) {
+        let mut paths = Vec::new();
+        flatten_use_tree(&item.tree, Vec::new(), &mut paths);
+        for segments in paths {
+            self.check_segments(item.span(), &segments);
+        }
+        visit::visit_item_use(self, item);
+    }
+
+    fn visit_item_extern_crate(&mut self, item: &'ast syn::ItemExternCrate) {
+        self.check_segments(item.span(), &[item.ident.to_string()]);
+        visit::visit_item_extern_crate(self, item);
+    }
+
+    fn visit_expr_call(&mut self, expr: &'ast syn::ExprCall) {
+        if let syn::Expr::Path(callee) = &*expr.func {
+            if let Some(last) = callee.path.segments.last() {
+                let name = last.ident.to_string();
+                if self.rules.banned_functions.contains(&name) {
+                    self.report(last.ident.span(), format!("function `{}`", name));
+                }
+            }
+        }
+        visit::visit_expr_call(self, expr);
+    }
+
+    fn visit_expr_method_call(&mut self, expr: &'ast syn::ExprMethodCall) {
+        let method = expr.method.to_string();
+        if self.rules.banned_functions.contains(&method) {
+            self.report(expr.method.span(), format!("method `{}`", method));
+        }
+        visit::visit_expr_method_call(self, expr);
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if let Some(name) = mac
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string())
+        {
+            if self.rules.banned_macros.contains(&name) {
+                self.report(mac.path.span(), format!("macro `{}!`", name));
+            }
+        }
+        visit::visit_macro(self, mac);
+        // 宏参数按逗号分隔的表达式（println!、format!、assert! 等）或 `expr; expr`（vec!）解析后继续检查。
+        // 无法按这两种形式解析的宏不做检查，格式字符串中内联的 `{name}` 也不会被检查
+        if let Ok(args) =
+            mac.parse_body_with(Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated)
+        {
+            for arg in &args {
+                self.visit_expr(arg);
+            }
+        } else if let Ok((value, len)) = mac.parse_body_with(parse_repeat) {
+            self.visit_expr(&value);
+            self.visit_expr(&len);
+        }
+    }
+}
+
+fn parse_repeat(input: ParseStream) -> syn::Result<(syn::Expr, syn::Expr)> {
+    let value = input.parse()?;
+    input.parse::<syn::Token![;]>()?;
+    let len = input.parse()?;
+    Ok((value, len))
+}
+
+fn split_path(path: &str) -> Vec<String> {
+    path.split("::")
+        .map(|segment| segment.trim().to_string())
+        .collect()
+}
+
+// 将 `use a::{b, c::d}` 展开为 [a, b]、[a, c, d]
+fn flatten_use_tree(tree: &syn::UseTree, prefix: Vec<String>, paths: &mut Vec<Vec<String>>) {
+    match tree {
+        syn::UseTree::Path(path) => {
+            let mut prefix = prefix;
+            prefix.push(path.ident.to_string());
+            flatten_use_tree(&path.tree, prefix, paths);
+        }
+        syn::UseTree::Name(name) => {
+            let mut prefix = prefix;
+            prefix.push(name.ident.to_string());
+            paths.push(prefix);
+        }
+        syn::UseTree::Rename(rename) => {
+            let mut prefix = prefix;
+            prefix.push(rename.ident.to_string());
+            paths.push(prefix);
+        }
+        syn::UseTree::Glob(_) => paths.push(prefix),
+        syn::UseTree::Group(group) => {
+            for tree in &group.items {
+                flatten_use_tree(tree, prefix.clone(), paths);
+            }
+        }
+    }
+}
+
+fn bin_op_symbol(op: &syn::BinOp) -> &'static str {
+    match op {
+        syn::BinOp::Add(_) => "+",
+        syn::BinOp::Sub(_) => "-",
+        syn::BinOp::Mul(_) => "*",
+        syn::BinOp::Div(_) => "/",
+        syn::BinOp::Rem(_) => "%",
+        syn::BinOp::And(_) => "&&",
+        syn::BinOp::Or(_) => "||",
+        syn::BinOp::BitXor(_) => "^",
+        syn::BinOp::BitAnd(_) => "&",
+        syn::BinOp::BitOr(_) => "|",
+        syn::BinOp::Shl(_) => "<<",
+        syn::BinOp::Shr(_) => ">>",
+        syn::BinOp::Eq(_) => "==",
+        syn::BinOp::Lt(_) => "<",
+        syn::BinOp::Le(_) => "<=",
+        syn::BinOp::Ne(_) => "!=",
+        syn::BinOp::Ge(_) => ">=",
+        syn::BinOp::Gt(_) => ">",
+        syn::BinOp::AddAssign(_) => "+=",
+        syn::BinOp::SubAssign(_) => "-=",
+        syn::BinOp::MulAssign(_) => "*=",
+        syn::BinOp::DivAssign(_) => "/=",
+        syn::BinOp::RemAssign(_) => "%=",
+        syn::BinOp::BitXorAssign(_) => "^=",
+        syn::BinOp::BitAndAssign(_) => "&=",
+        syn::BinOp::BitOrAssign(_) => "|=",
+        syn::BinOp::ShlAssign(_) => "<<=",
+        syn::BinOp::ShrAssign(_) => ">>=",
+        _ => "",
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{ExerciseConfig, Report};
+use serde_json::{json, Value};
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+// report.json 的当前版本。修改 Report、ExerciseResult 或 Statistics 的结构时递增，并在 migrate 中补充迁移步骤。
+//   1: 无 schema_version 字段，total_time 为整秒
+//   2: 增加 schema_version、statistics.total_time_ms、statistics.by_difficulty 与 exercises[].difficulty
+//   3: 增加可选的 exercises[].leaks
+//   4: 增加可选的 exercises[].toolchains 与 statistics.toolchains（工具链矩阵）
+//   5: 增加可选的 statistics.grade（百分比、等级与迟交惩罚）
+pub const SCHEMA_VERSION: u32 = 5;
+
+// 由 Rust 类型生成的 report.json 的 JSON Schema
+pub fn report_schema() -> Value {
+    let mut schema = serde_json::to_value(schemars::schema_for!(Report)).unwrap_or_default();
+    if let Some(object) = schema.as_object_mut() {
+        object.insert(
+            "$id".to_string(),
+            json!(format!("report.schema.v{}.json", SCHEMA_VERSION)),
+        );
+    }
+    schema
+}
+
+pub fn write_report_schema(path: &Path) -> io::Result<()> {
+    let mut json = serde_json::to_string_pretty(&report_schema())?;
+    json.push('\n');
+    fs::write(path, json)
+}
+
+// 读取任意版本的 report.json 并迁移到当前版本。
+// 提供配置文件时，为缺少难度信息的旧报告补全各题难度与按难度的小计。
+pub fn load_report(path: &Path, config: Option<&ExerciseConfig>) -> io::Result<Report> {
+    let value: Value = serde_json::from_reader(File::open(path)?)?;
+    let mut report: Report = serde_json::from_value(migrate(value)?)?;
+    if let Some(config) = config {
+        fill_difficulties(&mut report, config);
+    }
+    Ok(report)
+}
+
+// 逐个版本向前迁移。比当前版本更新的报告原样读取，不认识的字段会被忽略。
+pub fn migrate(mut value: Value) -> io::Result<Value> {
+    let Some(report) = value.as_object_mut() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "report is not a JSON object",
+        ));
+    };
+    let mut version = report
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .unwrap_or(1) as u32;
+    if version > SCHEMA_VERSION {
+        eprintln!(
+            "Warning: report schema version {} is newer than {}, unknown fields are ignored",
+            version, SCHEMA_VERSION
+        );
+    }
+
+    if version == 1 {
+        if let Some(statistics) = report.get_mut("statistics").and_then(Value::as_object_mut) {
+            let seconds = statistics
+                .get("total_time")
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+            statistics
+                .entry("total_time_ms")
+                .or_insert(json!(seconds * 1000));
+        }
+        version = 2;
+    }
+    // 之后的版本只增加了可选字段，缺失时取默认值
+    if version < SCHEMA_VERSION {
+        version = SCHEMA_VERSION;
+    }
+
+    report.insert(
+        "schema_version".to_string(),
+        json!(version.max(SCHEMA_VERSION)),
+    );
+    Ok(value)
+}
+
+fn fill_difficulties(report: &mut Report, config: &ExerciseConfig) {
+    let mut changed = false;
+    for exercise in report
+        .exercises
+        .iter_mut()
+        .filter(|e| e.difficulty.is_empty())
+    {
+        for (difficulty, exercises) in [
+            ("easy", &config.easy),
+            ("normal", &config.normal),
+            ("hard", &config.hard),
+        ] {
+            if exercises.iter().any(|e| e.name == exercise.name) {
+                exercise.difficulty = difficulty.to_string();
+                changed = true;
+            }
+        }
+    }
+    if !changed && !report.statistics.by_difficulty.is_empty() {
+        return;
+    }
+
+    report.statistics.by_difficulty.clear();
+    for exercise in &report.exercises {
+        if exercise.difficulty.is_empty() {
+            continue;
+        }
+        let max_score = [&config.easy, &config.normal, &config.hard]
+            .into_iter()
+            .flatten()
+            .find(|e| e.name == exercise.name)
+            .map_or(exercise.score, |e| e.score);
+        let subtotal = report
+            .statistics
+            .by_difficulty
+            .entry(exercise.difficulty.clone())
+            .or_default();
+        subtotal.exercises += 1;
+        if exercise.result {
+            subtotal.successes += 1;
+        } else {
+            subtotal.failures += 1;
+        }
+        subtotal.score += exercise.score;
+        subtotal.max_score += max_score;
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{ExerciseConfig, Subtotal};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+
+// 计分策略（写在 exercise_config.json 的 "scoring" 字段）
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Scoring {
+    // 各难度得分上限，例如 {"easy": 20, "normal": 30, "hard": 50}；未设置的难度以该难度各题分值之和为满分
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub caps: BTreeMap<String, i32>,
+    // 等级分段，按百分比从高到低匹配第一个 min_percentage 不高于成绩的等级
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grades: Vec<GradeBand>,
+    // 及格分（扣除迟交惩罚后的得分）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passing_score: Option<f64>,
+    // 本地截止时间，例如 "2025-03-31 23:59"（按本机时区）或带时区的 RFC 3339 时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub late_penalty: Option<LatePenalty>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GradeBand {
+    pub grade: String,
+    pub min_percentage: f64,
+}
+
+// 每迟交一天（不足一天按一天计）扣除 per_day 百分比的成绩，最多扣除 max 百分比
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LatePenalty {
+    pub per_day: f64,
+    #[serde(default = "default_max_penalty")]
+    pub max: f64,
+}
+
+fn default_max_penalty() -> f64 {
+    100.0
+}
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Default)]
+pub struct GradeSummary {
+    // 应用各难度上限后的得分与满分
+    pub capped_score: i32,
+    pub max_score: i32,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub late_days: u32,
+    // 迟交扣除的百分比
+    #[serde(default, skip_serializing_if = "is_zero_f64")]
+    pub penalty: f64,
+    pub final_score: f64,
+    pub percentage: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grade: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passed: Option<bool>,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+fn is_zero_f64(value: &f64) -> bool {
+    *value == 0.0
+}
+
+// 按计分策略计算百分比与等级。满分取自配置中的全部习题，提前退出 watch 模式时未评测的习题按 0 分计。
+pub fn grade(
+    config: &ExerciseConfig,
+    by_difficulty: &BTreeMap<String, Subtotal>,
+    now: DateTime<Local>,
+) -> io::Result<GradeSummary> {
+    let default_scoring = Scoring::default();
+    let scoring = config.scoring.as_ref().unwrap_or(&default_scoring);
+
+    let mut capped_score = 0;
+    let mut max_score = 0;
+    for (difficulty, exercises) in [
+        ("easy", &config.easy),
+        ("normal", &config.normal),
+        ("hard", &config.hard),
+    ] {
+        let full: i32 = exercises.iter().map(|e| e.score).sum();
+        let score = by_difficulty.get(difficulty).map_or(0, |s| s.score);
+        match scoring.caps.get(difficulty) {
+            Some(&cap) => {
+                capped_score += score.min(cap);
+                max_score += cap;
+            }
+            None => {
+                capped_score += score;
+                max_score += full;
+            }
+        }
+    }
+
+    let late_days = match &scoring.deadline {
+        Some(deadline) => days_late(parse_deadline(deadline)?, now),
+        None => 0,
+    };
+    let penalty = scoring.late_penalty.as_ref().map_or(0.0, |p| {
+        (p.per_day * late_days as f64).clamp(0.0, p.max.min(100.0))
+    });
+    let final_score = capped_score as f64 * (100.0 - penalty) / 100.0;
+    let percentage = if max_score > 0 {
+        final_score / max_score as f64 * 100.0
+    } else {
+        0.0
+    };
+
+    let mut bands = scoring.grades.clone();
+    bands.sort_by(|a, b| b.min_percentage.total_cmp(&a.min_percentage));
+    let grade = bands
+        .iter()
+        .find(|band| percentage >= band.min_percentage)
+        .map(|band| band.grade.clone());
+
+    Ok(GradeSummary {
+        capped_score,
+        max_score,
+        late_days,
+        penalty,
+        final_score,
+        percentage,
+        grade,
+        passed: scoring.passing_score.map(|min| final_score >= min),
+    })
+}
+
+fn parse_deadline(deadline: &str) -> io::Result<DateTime<Local>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(deadline) {
+        return Ok(time.with_timezone(&Local));
+    }
+    let naive = NaiveDateTime::parse_from_str(deadline, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(deadline, "%Y-%m-%d %H:%M"))
+        .or_else(|_| {
+            // 只写日期时截止到当天结束
+            NaiveDate::parse_from_str(deadline, "%Y-%m-%d")
+                .map(|date| date.and_hms_opt(23, 59, 59).unwrap_or_default())
+        })
+        .map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid deadline `{}`: {}", deadline, e),
+            )
+        })?;
+    Local.from_local_datetime(&naive).earliest().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "deadline `{}` does not exist in the local time zone",
+                deadline
+            ),
+        )
+    })
+}
+
+fn days_late(deadline: DateTime<Local>, now: DateTime<Local>) -> u32 {
+    let seconds = (now - deadline).num_seconds();
+    if seconds <= 0 {
+        0
+    } else {
+        ((seconds + 86399) / 86400) as u32
+    }
+}
@@ -0,0 +1,319 @@
+use crate::bundle;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+// 单个提交包的大小上限
+const MAX_BUNDLE_SIZE: u64 = 64 * 1024 * 1024;
+const RESULTS_NAME: &str = "results.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Queued,
+    Graded,
+    Error,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Submission {
+    pub id: usize,
+    pub name: String,
+    pub submitted_at: u64,
+    pub status: Status,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claimed_score: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub passed: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<String>,
+}
+
+// 本地结果存储：提交包保存在 <store>/bundles，评测结果保存在 <store>/results.json
+struct Store {
+    dir: PathBuf,
+    submissions: Vec<Submission>,
+}
+
+impl Store {
+    fn open(dir: &Path) -> io::Result<Store> {
+        fs::create_dir_all(dir.join("bundles"))?;
+        let results = dir.join(RESULTS_NAME);
+        let submissions = if results.exists() {
+            serde_json::from_reader(File::open(results)?)?
+        } else {
+            Vec::new()
+        };
+        Ok(Store {
+            dir: dir.to_path_buf(),
+            submissions,
+        })
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let file = File::create(self.dir.join(RESULTS_NAME))?;
+        serde_json::to_writer_pretty(file, &self.submissions)?;
+        Ok(())
+    }
+
+    fn bundle_path(&self, id: usize) -> PathBuf {
+        self.dir.join("bundles").join(format!("{}.tar.gz", id))
+    }
+
+    // 每位学生取最高分的已评测提交，按分数降序、提交时间升序排列
+    fn ranking(&self) -> Vec<&Submission> {
+        let mut best: BTreeMap<&str, &Submission> = BTreeMap::new();
+        for submission in self
+            .submissions
+            .iter()
+            .filter(|s| s.status == Status::Graded)
+        {
+            let entry = best.entry(&submission.name).or_insert(submission);
+            if submission.score > entry.score {
+                *entry = submission;
+            }
+        }
+        let mut ranking: Vec<&Submission> = best.into_values().collect();
+        ranking.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then(a.submitted_at.cmp(&b.submitted_at))
+        });
+        ranking
+    }
+}
+
+// 在本地启动评测服务：接收提交包，逐个在独立临时目录中重新评测，并提供排行榜页面
+pub fn serve(addr: &str, store_dir: &Path, official_config: &Path) -> io::Result<()> {
+    let store = Arc::new(Mutex::new(Store::open(store_dir)?));
+    let official_config = fs::canonicalize(official_config)?;
+    let server = Server::http(addr).map_err(|e| io::Error::other(e.to_string()))?;
+
+    let (queue, jobs) = mpsc::channel::<usize>();
+    {
+        // 重启后继续评测尚未完成的提交
+        let store = store.lock().unwrap();
+        for submission in store
+            .submissions
+            .iter()
+            .filter(|s| s.status == Status::Queued)
+        {
+            let _ = queue.send(submission.id);
+        }
+    }
+    let worker_store = Arc::clone(&store);
+    thread::spawn(move || {
+        for id in jobs {
+            grade(&worker_store, id, &official_config);
+        }
+    });
+
+    println!("Grading server listening on http://{}", addr);
+    for request in server.incoming_requests() {
+        if let Err(e) = handle(request, &store, &queue) {
+            eprintln!("Failed to handle request: {}", e);
+        }
+    }
+    Ok(())
+}
+
+fn grade(store: &Mutex<Store>, id: usize, official_config: &Path) {
+    let (name, bundle_path) = {
+        let store = store.lock().unwrap();
+        let name = store.submissions[id].name.clone();
+        (name, store.bundle_path(id))
+    };
+    println!("Grading submission #{} from {}", id, name);
+    let verification = bundle::verify(&bundle_path, Some(official_config));
+
+    let mut store = store.lock().unwrap();
+    let submission = &mut store.submissions[id];
+    match verification {
+        Ok(verification) => {
+            submission.status = Status::Graded;
+            submission.claimed_score = Some(verification.claimed.statistics.total_score);
+            submission.score = Some(verification.regraded.statistics.total_score);
+            submission.passed = Some(verification.regraded.statistics.total_successes);
+            submission.problems = verification.problems;
+        }
+        Err(e) => {
+            submission.status = Status::Error;
+            submission.problems = vec![e.to_string()];
+        }
+    }
+    println!(
+        "Submission #{} from {}: {:?}, score {:?}",
+        id, name, submission.status, submission.score
+    );
+    if let Err(e) = store.save() {
+        eprintln!("Failed to save results: {}", e);
+    }
+}
+
+fn handle(mut request: Request, store: &Mutex<Store>, queue: &Sender<usize>) -> io::Result<()> {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    match (request.method(), path) {
+        (Method::Get, "/") => {
+            let page = ranking_page(&store.lock().unwrap());
+            request.respond(
+                Response::from_string(page).with_header(content_type("text/html; charset=utf-8")),
+            )
+        }
+        (Method::Get, "/results.json") => {
+            let json = serde_json::to_string_pretty(&store.lock().unwrap().submissions)?;
+            request
+                .respond(Response::from_string(json).with_header(content_type("application/json")))
+        }
+        (Method::Post, "/submit") => {
+            let name = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("name="))
+                .unwrap_or_default()
+                .to_string();
+            if !is_valid_name(&name) {
+                return request.respond(
+                    Response::from_string(
+                        "missing or invalid `name` (use letters, digits, `-` or `_`)\n",
+                    )
+                    .with_status_code(400),
+                );
+            }
+            let mut body = Vec::new();
+            request
+                .as_reader()
+                .take(MAX_BUNDLE_SIZE + 1)
+                .read_to_end(&mut body)?;
+            if body.len() as u64 > MAX_BUNDLE_SIZE {
+                return request
+                    .respond(Response::from_string("bundle too large\n").with_status_code(413));
+            }
+
+            let id = {
+                let mut store = store.lock().unwrap();
+                let id = store.submissions.len();
+                fs::write(store.bundle_path(id), &body)?;
+                store.submissions.push(Submission {
+                    id,
+                    name: name.clone(),
+                    submitted_at: now(),
+                    status: Status::Queued,
+                    claimed_score: None,
+                    score: None,
+                    passed: None,
+                    problems: Vec::new(),
+                });
+                store.save()?;
+                id
+            };
+            let _ = queue.send(id);
+            println!("Queued submission #{} from {}", id, name);
+            request.respond(
+                Response::from_string(format!("{{\"id\": {}, \"status\": \"queued\"}}\n", id))
+                    .with_status_code(202)
+                    .with_header(content_type("application/json")),
+            )
+        }
+        _ => request.respond(Response::from_string("not found\n").with_status_code(404)),
+    }
+}
+
+fn ranking_page(store: &Store) -> String {
+    let mut rows = String::new();
+    for (rank, submission) in store.ranking().iter().enumerate() {
+        rows.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            rank + 1,
+            submission.name,
+            submission.score.unwrap_or(0),
+            submission.passed.unwrap_or(0),
+            format_timestamp(submission.submitted_at),
+            if submission.problems.is_empty() {
+                "verified".to_string()
+            } else {
+                format!("{} problem(s)", submission.problems.len())
+            }
+        ));
+    }
+    let pending = store
+        .submissions
+        .iter()
+        .filter(|s| s.status == Status::Queued)
+        .count();
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<meta http-equiv="refresh" content="10">
+<title>Rust 训练营排行榜</title>
+<style>
+body {{ font-family: sans-serif; margin: 2em; }}
+table {{ border-collapse: collapse; }}
+th, td {{ border: 1px solid #ccc; padding: 4px 12px; text-align: left; }}
+</style>
+</head>
+<body>
+<h1>排行榜</h1>
+<p>{} submission(s) waiting to be graded.</p>
+<table>
+<tr><th>#</th><th>Name</th><th>Score</th><th>Passed</th><th>Submitted (UTC)</th><th>Verification</th></tr>
+{}</table>
+</body>
+</html>
+"#,
+        pending, rows
+    )
+}
+
+fn content_type(value: &str) -> Header {
+    Header::from_bytes("Content-Type", value).unwrap()
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// Unix 时间戳格式化为 UTC 时间（civil-from-days 算法）
+fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86400) as i64;
+    let seconds = secs % 86400;
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
@@ -0,0 +1,100 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use syn::spanned::Spanned;
+
+// 一份已解析的题解源码
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub text: String,
+    pub ast: syn::File,
+}
+
+impl SourceFile {
+    pub fn load(path: &Path) -> io::Result<SourceFile> {
+        let text = fs::read_to_string(path)?;
+        let ast = syn::parse_file(&text).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{}: {}", path.display(), e.span().start().line, e),
+            )
+        })?;
+        Ok(SourceFile {
+            path: path.to_path_buf(),
+            text,
+            ast,
+        })
+    }
+
+    // 去掉 #[cfg(test)] 条目后的题解代码
+    pub fn solution_items(&self) -> impl Iterator<Item = &syn::Item> {
+        self.ast.items.iter().filter(|item| !is_test_item(item))
+    }
+
+    // #[cfg(test)] 条目所占的行区间（闭区间）
+    pub fn test_line_ranges(&self) -> Vec<(usize, usize)> {
+        self.ast
+            .items
+            .iter()
+            .filter(|item| is_test_item(item))
+            .map(|item| {
+                let span = item.span();
+                (span.start().line, span.end().line)
+            })
+            .collect()
+    }
+}
+
+// 题解源码文件：单文件习题为文件本身，Cargo 项目为 src 下除 tests.rs 外的所有 .rs 文件
+pub fn solution_paths(exercise_path: &Path, exercise_type: &str) -> io::Result<Vec<PathBuf>> {
+    match exercise_type {
+        "single_file" => Ok(vec![exercise_path.to_path_buf()]),
+        "cargo_project" => {
+            let mut paths: Vec<PathBuf> = fs::read_dir(exercise_path.join("src"))?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "rs"))
+                .filter(|path| path.file_name().is_some_and(|name| name != "tests.rs"))
+                .collect();
+            paths.sort();
+            Ok(paths)
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Unknown exercise type: {}", exercise_type),
+        )),
+    }
+}
+
+pub fn load_solution_sources(
+    exercise_path: &Path,
+    exercise_type: &str,
+) -> io::Result<Vec<SourceFile>> {
+    solution_paths(exercise_path, exercise_type)?
+        .iter()
+        .map(|path| SourceFile::load(path))
+        .collect()
+}
+
+pub fn is_test_item(item: &syn::Item) -> bool {
+    let attrs = match item {
+        syn::Item::Mod(item) => &item.attrs,
+        syn::Item::Fn(item) => &item.attrs,
+        syn::Item::Use(item) => &item.attrs,
+        syn::Item::Impl(item) => &item.attrs,
+        syn::Item::Const(item) => &item.attrs,
+        syn::Item::Static(item) => &item.attrs,
+        syn::Item::Struct(item) => &item.attrs,
+        _ => return false,
+    };
+    attrs.iter().any(is_cfg_test)
+}
+
+fn is_cfg_test(attr: &syn::Attribute) -> bool {
+    if !attr.path().is_ident("cfg") {
+        return false;
+    }
+    match attr.parse_args::<syn::Ident>() {
+        Ok(ident) => ident == "test",
+        Err(_) => false,
+    }
+}
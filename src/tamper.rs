@@ -0,0 +1,37 @@
+use crate::source::SourceFile;
+use proc_macro2::TokenStream;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// 计算受保护测试代码的校验和：单文件习题为其中的 #[cfg(test)] 条目，Cargo 项目为 src/tests.rs。
+// 校验基于 token 序列，只改动空白或普通注释不会影响结果。
+pub fn test_checksum(exercise_path: &Path, exercise_type: &str) -> io::Result<String> {
+    let protected = match exercise_type {
+        "single_file" => {
+            let source = SourceFile::load(exercise_path)?;
+            let lines: Vec<&str> = source.text.lines().collect();
+            let mut region = String::new();
+            for (start, end) in source.test_line_ranges() {
+                region.push_str(&lines[start - 1..end.min(lines.len())].join("\n"));
+                region.push('\n');
+            }
+            region
+        }
+        "cargo_project" => fs::read_to_string(exercise_path.join("src").join("tests.rs"))?,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unknown exercise type: {}", exercise_type),
+            ))
+        }
+    };
+    let tokens: TokenStream = protected
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)))?;
+    Ok(format!(
+        "{:x}",
+        Sha256::digest(tokens.to_string().as_bytes())
+    ))
+}
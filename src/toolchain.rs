@@ -0,0 +1,66 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+// 某个工具链上的评测汇总
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
+pub struct ToolchainSummary {
+    pub toolchain: String,
+    pub version: String,
+    pub successes: usize,
+    pub failures: usize,
+}
+
+// 构造使用指定工具链的 rustc/cargo 命令（通过 rustup 代理的 `+toolchain` 参数），None 表示默认工具链
+pub fn command(program: &str, toolchain: Option<&str>) -> Command {
+    let mut command = Command::new(program);
+    if let Some(toolchain) = toolchain {
+        command.arg(format!("+{}", toolchain));
+    }
+    command
+}
+
+// 从配置的工具链中筛选出本地已安装的，未安装的给出提示后跳过（评测器不会自动下载工具链）
+pub fn installed_only(requested: &[String]) -> Vec<String> {
+    if requested.is_empty() {
+        return Vec::new();
+    }
+    let installed: Vec<String> = match Command::new("rustup").args(["toolchain", "list"]).output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .map(str::to_string)
+            .collect(),
+        _ => {
+            eprintln!("Warning: rustup is not available, skipping the toolchain matrix");
+            return Vec::new();
+        }
+    };
+    requested
+        .iter()
+        .filter(|toolchain| {
+            // `stable` 对应 `stable-x86_64-unknown-linux-gnu`，`1.70` 对应 `1.70-…` 或 `1.70.0-…`
+            let found = installed.iter().any(|name| {
+                name == *toolchain
+                    || name.starts_with(&format!("{}-", toolchain))
+                    || name.starts_with(&format!("{}.0-", toolchain))
+            });
+            if !found {
+                eprintln!(
+                    "Warning: toolchain `{}` is not installed, skipping it (install it with `rustup toolchain install {}`)",
+                    toolchain, toolchain
+                );
+            }
+            found
+        })
+        .cloned()
+        .collect()
+}
+
+pub fn version(toolchain: &str) -> String {
+    command("rustc", Some(toolchain))
+        .arg("--version")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_default()
+}
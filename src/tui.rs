@@ -0,0 +1,517 @@
+use crate::harness::temp_dir;
+use crate::{schema, source, Exercise, ExerciseConfig, ExerciseResult};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Status {
+    NotRun,
+    Queued,
+    Running,
+    Passed,
+    Failed,
+}
+
+struct Entry {
+    difficulty: &'static str,
+    exercise: Exercise,
+    status: Status,
+    result: Option<ExerciseResult>,
+    output: String,
+    elapsed: Option<Duration>,
+}
+
+// 评测线程返回的消息
+enum Message {
+    Started(usize),
+    Finished {
+        index: usize,
+        output: String,
+        result: Option<ExerciseResult>,
+        elapsed: Duration,
+    },
+}
+
+struct App {
+    entries: Vec<Entry>,
+    selected: usize,
+    scroll: u16,
+    jobs: Sender<(usize, String)>,
+    messages: Receiver<Message>,
+    notice: String,
+}
+
+// 全屏终端界面：按难度分组展示习题状态，可单独重新评测、重新评测失败的习题或在编辑器中打开
+pub fn run(config: ExerciseConfig) -> io::Result<()> {
+    // 用上一次 report.json 中的结果初始化状态
+    let mut last_results = schema::load_report(Path::new("report.json"), Some(&config))
+        .map(|report| report.exercises)
+        .unwrap_or_default();
+    let mut entries = Vec::new();
+    for (difficulty, exercises) in [
+        ("easy", config.easy),
+        ("normal", config.normal),
+        ("hard", config.hard),
+    ] {
+        for exercise in exercises {
+            let result = last_results
+                .iter()
+                .position(|r| r.name == exercise.name)
+                .map(|index| last_results.swap_remove(index));
+            entries.push(Entry {
+                difficulty,
+                status: match &result {
+                    Some(r) if r.result => Status::Passed,
+                    Some(_) => Status::Failed,
+                    None => Status::NotRun,
+                },
+                exercise,
+                result,
+                output: String::new(),
+                elapsed: None,
+            });
+        }
+    }
+
+    let (jobs, job_queue) = mpsc::channel();
+    let (reply, messages) = mpsc::channel();
+    thread::spawn(move || worker(job_queue, reply));
+
+    let mut app = App {
+        entries,
+        selected: 0,
+        scroll: 0,
+        jobs,
+        messages,
+        notice: String::new(),
+    };
+
+    // panic 时先恢复终端，否则错误信息会被全屏界面吞掉
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = leave();
+        default_hook(info);
+    }));
+
+    let mut terminal = enter()?;
+    let result = app.event_loop(&mut terminal);
+    leave()?;
+    result
+}
+
+impl App {
+    fn event_loop(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        loop {
+            while let Ok(message) = self.messages.try_recv() {
+                self.apply(message);
+            }
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if !event::poll(POLL_INTERVAL)? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Up | KeyCode::Char('k') => self.select(self.selected.saturating_sub(1)),
+                KeyCode::Down | KeyCode::Char('j') => self.select(self.selected + 1),
+                KeyCode::Home => self.select(0),
+                KeyCode::End => self.select(self.entries.len().saturating_sub(1)),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                KeyCode::Enter | KeyCode::Char('r') => self.enqueue(self.selected),
+                KeyCode::Char('f') => {
+                    let failed: Vec<usize> = (0..self.entries.len())
+                        .filter(|&i| self.entries[i].status == Status::Failed)
+                        .collect();
+                    self.notice = format!("Rerunning {} failed exercise(s)", failed.len());
+                    for index in failed {
+                        self.enqueue(index);
+                    }
+                }
+                KeyCode::Char('a') => {
+                    for index in 0..self.entries.len() {
+                        self.enqueue(index);
+                    }
+                }
+                KeyCode::Char('e') => {
+                    leave()?;
+                    let opened = self.open_in_editor();
+                    *terminal = enter()?;
+                    self.notice = match opened {
+                        Ok(()) => String::new(),
+                        Err(e) => format!("Failed to open editor: {}", e),
+                    };
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        if index < self.entries.len() && index != self.selected {
+            self.selected = index;
+            self.scroll = 0;
+        }
+    }
+
+    fn enqueue(&mut self, index: usize) {
+        let entry = &mut self.entries[index];
+        if matches!(entry.status, Status::Queued | Status::Running) {
+            return;
+        }
+        entry.status = Status::Queued;
+        let _ = self.jobs.send((index, entry.exercise.name.clone()));
+    }
+
+    fn apply(&mut self, message: Message) {
+        match message {
+            Message::Started(index) => self.entries[index].status = Status::Running,
+            Message::Finished {
+                index,
+                output,
+                result,
+                elapsed,
+            } => {
+                let entry = &mut self.entries[index];
+                entry.status = match &result {
+                    Some(r) if r.result => Status::Passed,
+                    _ => Status::Failed,
+                };
+                entry.result = result;
+                entry.output = output;
+                entry.elapsed = Some(elapsed);
+            }
+        }
+    }
+
+    // 暂时退出全屏界面，用 $EDITOR 打开题解源码
+    fn open_in_editor(&self) -> io::Result<()> {
+        let exercise = &self.entries[self.selected].exercise;
+        let path = Path::new("./exercises").join(&exercise.path);
+        let file = source::solution_paths(&path, &exercise.exercise_type)?
+            .into_iter()
+            .next()
+            .unwrap_or(path);
+        let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+        let mut parts = editor.split_whitespace();
+        let program = parts.next().unwrap_or("vi");
+        let status = Command::new(program).args(parts).arg(&file).status()?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(io::Error::other(format!(
+                "{} exited with {}",
+                editor, status
+            )))
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [list_area, detail_area] =
+            Layout::horizontal([Constraint::Length(32), Constraint::Min(1)]).areas(main);
+
+        // 左侧习题列表，按难度分组
+        let mut items = Vec::new();
+        let mut selected_item = 0;
+        let mut current_group = "";
+        for (index, entry) in self.entries.iter().enumerate() {
+            if entry.difficulty != current_group {
+                current_group = entry.difficulty;
+                let (passed, total) = self.group_progress(current_group);
+                items.push(ListItem::new(Line::from(Span::styled(
+                    format!("{} ({}/{})", current_group, passed, total),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ))));
+            }
+            if index == self.selected {
+                selected_item = items.len();
+            }
+            let (symbol, color) = status_symbol(entry.status);
+            let score = entry.result.as_ref().map_or(0, |r| r.score);
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled(format!("  {} ", symbol), Style::default().fg(color)),
+                Span::raw(format!("{:<18}", entry.exercise.name)),
+                Span::raw(format!("{:>3}/{:<3}", score, entry.exercise.score)),
+            ])));
+        }
+        let total_score: i32 = self
+            .entries
+            .iter()
+            .filter_map(|e| e.result.as_ref())
+            .map(|r| r.score)
+            .sum();
+        let max_score: i32 = self.entries.iter().map(|e| e.exercise.score).sum();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!(" Exercises {}/{} ", total_score, max_score)))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(selected_item));
+        frame.render_stateful_widget(list, list_area, &mut state);
+
+        // 右侧详情：提示、诊断信息与上一次的输出
+        let entry = &self.entries[self.selected];
+        let detail = Paragraph::new(detail_lines(entry))
+            .block(Block::bordered().title(format!(" {} ", entry.exercise.name)))
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
+        frame.render_widget(detail, detail_area);
+
+        let help =
+            "↑/↓ select  r rerun  f rerun failed  a rerun all  e edit  PgUp/PgDn scroll  q quit";
+        let footer_text = if self.notice.is_empty() {
+            help.to_string()
+        } else {
+            format!("{}  |  {}", self.notice, help)
+        };
+        frame.render_widget(
+            Paragraph::new(footer_text).style(Style::default().fg(Color::DarkGray)),
+            footer,
+        );
+    }
+
+    fn group_progress(&self, difficulty: &str) -> (usize, usize) {
+        let group: Vec<&Entry> = self
+            .entries
+            .iter()
+            .filter(|e| e.difficulty == difficulty)
+            .collect();
+        let passed = group.iter().filter(|e| e.status == Status::Passed).count();
+        (passed, group.len())
+    }
+}
+
+fn status_symbol(status: Status) -> (&'static str, Color) {
+    match status {
+        Status::NotRun => ("·", Color::DarkGray),
+        Status::Queued => ("…", Color::Yellow),
+        Status::Running => ("▶", Color::Yellow),
+        Status::Passed => ("✓", Color::Green),
+        Status::Failed => ("✗", Color::Red),
+    }
+}
+
+fn detail_lines(entry: &Entry) -> Vec<Line<'static>> {
+    let heading = |text: &str| {
+        Line::from(Span::styled(
+            text.to_string(),
+            Style::default().add_modifier(Modifier::BOLD),
+        ))
+    };
+    let (_, color) = status_symbol(entry.status);
+    let status = match entry.status {
+        Status::NotRun => "not run",
+        Status::Queued => "queued",
+        Status::Running => "running",
+        Status::Passed => "passed",
+        Status::Failed => "failed",
+    };
+    let mut lines = vec![Line::from(vec![
+        Span::raw(format!(
+            "{} · {} · ",
+            entry.difficulty, entry.exercise.exercise_type
+        )),
+        Span::styled(status.to_string(), Style::default().fg(color)),
+        Span::raw(match entry.elapsed {
+            Some(elapsed) => format!(" · {:.1}s", elapsed.as_secs_f64()),
+            None => String::new(),
+        }),
+    ])];
+
+    let hints = hints(entry);
+    if !hints.is_empty() {
+        lines.push(Line::default());
+        lines.push(heading("Hints"));
+        lines.extend(
+            hints
+                .into_iter()
+                .map(|hint| Line::from(format!("  {}", hint))),
+        );
+    }
+
+    if let Some(result) = &entry.result {
+        let diagnostics: Vec<String> = result
+            .diagnostics
+            .iter()
+            .chain(&result.violations)
+            .map(|d| d.summary())
+            .collect();
+        if !diagnostics.is_empty() {
+            lines.push(Line::default());
+            lines.push(heading("Diagnostics"));
+            lines.extend(diagnostics.into_iter().map(|d| {
+                Line::from(Span::styled(
+                    format!("  {}", d),
+                    Style::default().fg(Color::Red),
+                ))
+            }));
+        }
+    }
+
+    lines.push(Line::default());
+    lines.push(heading("Output"));
+    if entry.output.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  (press r to run this exercise)",
+            Style::default().fg(Color::DarkGray),
+        )));
+    } else {
+        lines.extend(
+            entry
+                .output
+                .lines()
+                .map(|line| Line::from(line.to_string())),
+        );
+    }
+    lines
+}
+
+// 根据评测结果生成提示：配置文件中的提示、错误码说明、规则、复杂度与差分测试的反例
+fn hints(entry: &Entry) -> Vec<String> {
+    let Some(result) = entry.result.as_ref().filter(|r| !r.result) else {
+        return Vec::new();
+    };
+    let mut hints = Vec::new();
+    if let Some(hint) = &entry.exercise.hint {
+        hints.push(hint.clone());
+    }
+    if result.tampered {
+        hints.push(
+            "The protected test code was modified; restore it with `git checkout`.".to_string(),
+        );
+    }
+    if let Some(code) = result.diagnostics.iter().find_map(|d| d.code.as_ref()) {
+        hints.push(format!(
+            "Run `cargo run explain {}` for a detailed explanation.",
+            code
+        ));
+    }
+    if let Some(reason) = entry
+        .exercise
+        .rules
+        .as_ref()
+        .and_then(|r| r.reason.as_ref())
+    {
+        if !result.violations.is_empty() {
+            hints.push(reason.clone());
+        }
+    }
+    if let Some(leaks) = result.leaks.as_ref().filter(|l| !l.is_clean()) {
+        hints.push(format!(
+            "The tests leak {} allocation(s) ({} bytes); free what you allocate, e.g. by implementing Drop.",
+            leaks.allocations, leaks.bytes
+        ));
+    }
+    if let Some(complexity) = result.complexity.as_ref().filter(|c| c.exceeded) {
+        hints.push(format!(
+            "Running time grows like n^{:.2}, but {} is expected.",
+            complexity.measured_exponent, complexity.declared
+        ));
+    }
+    if let Some(counterexample) = result
+        .differential
+        .as_ref()
+        .and_then(|d| d.counterexample.as_ref())
+    {
+        hints.push(format!(
+            "Differs from the reference for input {}",
+            counterexample.input
+        ));
+        hints.push(format!("  expected: {}", counterexample.expected));
+        hints.push(format!("  actual:   {}", counterexample.actual));
+    }
+    hints
+}
+
+// 逐个在子进程中评测，避免评测输出破坏界面
+fn worker(jobs: Receiver<(usize, String)>, reply: Sender<Message>) {
+    for (index, name) in jobs {
+        let _ = reply.send(Message::Started(index));
+        let start = Instant::now();
+        let (output, result) = match grade_in_subprocess(&name) {
+            Ok(graded) => graded,
+            Err(e) => (format!("Failed to grade {}: {}", name, e), None),
+        };
+        let _ = reply.send(Message::Finished {
+            index,
+            output,
+            result,
+            elapsed: start.elapsed(),
+        });
+    }
+}
+
+fn grade_in_subprocess(name: &str) -> io::Result<(String, Option<ExerciseResult>)> {
+    let dir = temp_dir("tui")?;
+    let result_path = dir.join("result.json");
+    let output = Command::new(std::env::current_exe()?)
+        .arg("grade-one")
+        .arg(name)
+        .arg(&result_path)
+        .output();
+    let result = fs::read(&result_path)
+        .ok()
+        .and_then(|json| serde_json::from_slice(&json).ok());
+    let _ = fs::remove_dir_all(&dir);
+    let output = output?;
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok((strip_ansi(text.trim_start()), result))
+}
+
+// 去掉评测输出中的终端颜色控制序列
+fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+fn enter() -> io::Result<DefaultTerminal> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = DefaultTerminal::new(ratatui::backend::CrosstermBackend::new(io::stdout()))?;
+    terminal.clear()?;
+    Ok(terminal)
+}
+
+fn leave() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)
+}